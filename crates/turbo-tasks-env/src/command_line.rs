@@ -1,18 +1,35 @@
 use std::env;
 
+use anyhow::Result;
 use indexmap::IndexMap;
+use turbo_tasks::primitives::OptionStringVc;
 
-use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc, GLOBAL_ENV_LOCK};
+use crate::{
+    is_sensitive, EnvMapVc, OptionSourcedValueVc, ProcessEnv, ProcessEnvVc, SourceLabel,
+    GLOBAL_ENV_LOCK,
+};
 
 /// Load the environment variables defined via command line.
+///
+/// With `exclude_sensitive`, variables whose names look like they hold secrets,
+/// e.g. `DATABASE_PASSWORD` or `AWS_ACCESS_KEY_ID`, are left out of
+/// [ProcessEnv::read_all], so they don't end up in the cache. They can still be
+/// read explicitly via [ProcessEnv::read].
 #[turbo_tasks::value]
-pub struct CommandLineProcessEnv;
+pub struct CommandLineProcessEnv {
+    exclude_sensitive: bool,
+}
 
 #[turbo_tasks::value_impl]
 impl CommandLineProcessEnvVc {
     #[turbo_tasks::function]
     pub fn new() -> Self {
-        CommandLineProcessEnv.cell()
+        Self::with_options(false)
+    }
+
+    #[turbo_tasks::function]
+    pub fn with_options(exclude_sensitive: bool) -> Self {
+        CommandLineProcessEnv { exclude_sensitive }.cell()
     }
 }
 
@@ -26,6 +43,58 @@ fn env_snapshot() -> IndexMap<String, String> {
 impl ProcessEnv for CommandLineProcessEnv {
     #[turbo_tasks::function]
     fn read_all(&self) -> EnvMapVc {
-        EnvMapVc::cell(env_snapshot())
+        let mut vars = env_snapshot();
+        if self.exclude_sensitive {
+            vars.retain(|name, _| !is_sensitive(name));
+        }
+        EnvMapVc::cell(vars)
+    }
+
+    #[turbo_tasks::function]
+    fn read_all_filtered(&self, prefix: &str) -> EnvMapVc {
+        // Only the matching variables are converted, the others never leave the
+        // process env.
+        let prefix = prefix.to_uppercase();
+        let _lock = GLOBAL_ENV_LOCK.lock().unwrap();
+        let vars = env::vars_os()
+            .filter_map(|(name, value)| {
+                let name = name.into_string().ok()?;
+                if !name.to_uppercase().starts_with(&prefix)
+                    || (self.exclude_sensitive && is_sensitive(&name))
+                {
+                    return None;
+                }
+                Some((name, value.into_string().ok()?))
+            })
+            .collect();
+        EnvMapVc::cell(vars)
+    }
+
+    #[turbo_tasks::function]
+    fn read(&self, name: &str) -> OptionStringVc {
+        // Looks up the single variable instead of snapshotting the env. Sensitive
+        // variables are returned too, as they are read explicitly. Names are
+        // matched as given or in upper case.
+        let _lock = GLOBAL_ENV_LOCK.lock().unwrap();
+        OptionStringVc::cell(
+            env::var(name)
+                .or_else(|_| env::var(name.to_uppercase()))
+                .ok(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn read_with_source(
+        self_vc: CommandLineProcessEnvVc,
+        name: &str,
+    ) -> Result<OptionSourcedValueVc> {
+        Ok(OptionSourcedValueVc::cell(
+            self_vc
+                .as_process_env()
+                .read(name)
+                .await?
+                .as_ref()
+                .map(|value| (value.clone(), SourceLabel::CommandLine)),
+        ))
     }
 }
@@ -0,0 +1,56 @@
+use indexmap::IndexMap;
+
+use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+
+/// A flat env with a fixed set of variables.
+#[turbo_tasks::value]
+pub struct CustomProcessEnv {
+    vars: EnvMapVc,
+}
+
+#[turbo_tasks::value_impl]
+impl CustomProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(vars: EnvMapVc) -> Self {
+        CustomProcessEnv { vars }.cell()
+    }
+}
+
+impl CustomProcessEnvVc {
+    /// Returns the `parent` env with the `overrides` on top, e.g. to run a
+    /// test with a single variable changed. Variables that aren't overridden
+    /// are read from `parent`.
+    ///
+    /// The returned env is a [LayeredProcessEnvVc](crate::LayeredProcessEnvVc)
+    /// of `parent` and a [CustomProcessEnvVc] holding the `overrides`, see
+    /// [ProcessEnv::overlay]. The example needs a turbo-tasks runtime, so it's
+    /// only compiled.
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use turbo_tasks_env::{CommandLineProcessEnvVc, CustomProcessEnvVc};
+    /// # async fn example() -> Result<()> {
+    /// let env = CustomProcessEnvVc::over(
+    ///     CommandLineProcessEnvVc::new().into(),
+    ///     &[("NODE_ENV", "production")],
+    /// );
+    /// assert_eq!(env.read("NODE_ENV").await?.as_deref(), Some("production"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn over(parent: ProcessEnvVc, overrides: &[(&str, &str)]) -> ProcessEnvVc {
+        let vars = overrides
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<IndexMap<_, _>>();
+        parent.overlay(CustomProcessEnvVc::new(EnvMapVc::cell(vars)).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for CustomProcessEnv {
+    #[turbo_tasks::function]
+    fn read_all(&self) -> EnvMapVc {
+        self.vars
+    }
+}
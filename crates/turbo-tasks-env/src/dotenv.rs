@@ -1,26 +1,181 @@
-use std::env;
+use std::{borrow::Cow, env};
 
 use anyhow::{anyhow, Context, Result};
 use indexmap::IndexMap;
-use turbo_tasks::ValueToString;
+use turbo_tasks::{primitives::StringVc, ValueToString};
 use turbo_tasks_fs::{FileContent, FileSystemPathVc};
 
-use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc, GLOBAL_ENV_LOCK};
+use crate::{
+    is_sensitive, EnvMapVc, OptionSourcedValueVc, ProcessEnv, ProcessEnvVc, SecretDecryptorVc,
+    SourceLabel, GLOBAL_ENV_LOCK, MASKED_VALUE,
+};
+
+/// Options controlling how the variables of a dotenv file are loaded.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct DotenvOptions {
+    /// Variables with an empty value (`FOO=`) are treated as if they weren't
+    /// defined in the dotenv file at all.
+    pub empty_is_unset: bool,
+    /// Normalizes the case of the variable names as they are loaded.
+    pub key_case: KeyCase,
+    /// Decrypts the values starting with `secret_marker`, so the dotenv file
+    /// doesn't have to contain secrets in plain text. Other values, and values
+    /// taken from the prior env, are kept as they are. Values referencing an
+    /// encrypted variable (`${SECRET}`) see the encrypted value.
+    pub decryptor: Option<SecretDecryptorVc>,
+    /// The prefix marking encrypted values, `enc:` by default.
+    pub secret_marker: String,
+    /// Substitutes references to other variables (`${HOST}` or `$HOST`) in
+    /// unquoted and double-quoted values, which is the default. Otherwise
+    /// every value is loaded verbatim, e.g. for templates containing `${...}`.
+    /// Escape sequences like `\$` are still applied.
+    pub interpolate: bool,
+}
+
+impl Default for DotenvOptions {
+    fn default() -> Self {
+        Self {
+            empty_is_unset: false,
+            key_case: KeyCase::default(),
+            decryptor: None,
+            secret_marker: "enc:".to_string(),
+            interpolate: true,
+        }
+    }
+}
+
+/// How the names of the variables of a dotenv file are normalized.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Copy, Debug, Default)]
+pub enum KeyCase {
+    /// The names are kept as written.
+    #[default]
+    Preserve,
+    /// The names are uppercased, e.g. `db_host` is loaded as `DB_HOST`.
+    Upper,
+    /// The names are lowercased.
+    Lower,
+}
+
+impl KeyCase {
+    fn apply(self, key: String) -> String {
+        match self {
+            KeyCase::Preserve => key,
+            KeyCase::Upper => key.to_uppercase(),
+            KeyCase::Lower => key.to_lowercase(),
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DotenvOptionsVc {
+    #[turbo_tasks::function]
+    pub fn default() -> Self {
+        Self::cell(Default::default())
+    }
+}
+
+impl Default for DotenvOptionsVc {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// Where the content of a [DotenvProcessEnv] comes from.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+enum DotenvSource {
+    /// A dotenv file, which might not exist.
+    File(FileSystemPathVc),
+    /// The content of a dotenv file.
+    String(String),
+}
 
 /// Load the environment variables defined via a dotenv file, with an
 /// optional prior state that we can lookup already defined variables
 /// from.
+///
+/// The file is read via [FileSystemPathVc::read], so the variables are
+/// recomputed when the file changes.
+///
+/// An unquoted value ends at an inline comment, which is a `#` preceded by
+/// whitespace (`PORT=3000 # the port` defines `3000`). Any other `#` is kept
+/// literally, both in unquoted values (`COLOR=#fff`, `URL=http://x#frag`) and
+/// in quoted values (`Q="a # b"`).
+///
+/// Whitespace around the name is trimmed, as is whitespace around an unquoted
+/// value, so `  FOO = bar  ` defines `FOO` as `bar`. Quoted values keep their
+/// inner whitespace (`FOO="  bar  "`). Whitespace inside an unquoted value is
+/// an error, such values have to be quoted.
+///
+/// A variable defined twice keeps its first value. With a [KeyCase] other than
+/// [KeyCase::Preserve], defining two variables whose names only differ in case
+/// is an error.
 #[turbo_tasks::value]
 pub struct DotenvProcessEnv {
     prior: Option<ProcessEnvVc>,
-    path: FileSystemPathVc,
+    source: DotenvSource,
+    options: DotenvOptionsVc,
 }
 
 #[turbo_tasks::value_impl]
 impl DotenvProcessEnvVc {
     #[turbo_tasks::function]
     pub fn new(prior: Option<ProcessEnvVc>, path: FileSystemPathVc) -> Self {
-        DotenvProcessEnv { prior, path }.cell()
+        Self::with_options(prior, path, DotenvOptionsVc::default())
+    }
+
+    #[turbo_tasks::function]
+    pub fn with_options(
+        prior: Option<ProcessEnvVc>,
+        path: FileSystemPathVc,
+        options: DotenvOptionsVc,
+    ) -> Self {
+        DotenvProcessEnv {
+            prior,
+            source: DotenvSource::File(path),
+            options,
+        }
+        .cell()
+    }
+
+    /// Loads the environment variables defined by the dotenv `content`
+    /// instead of reading them from a file.
+    #[turbo_tasks::function]
+    pub fn from_string(content: String, prior: Option<ProcessEnvVc>) -> Self {
+        DotenvProcessEnv {
+            prior,
+            source: DotenvSource::String(content),
+            options: DotenvOptionsVc::default(),
+        }
+        .cell()
+    }
+
+    /// Loads the dotenv files of `dir` like Vite does for `mode`, from the
+    /// highest to the lowest precedence:
+    ///
+    /// 1. `parent`, so variables that are already defined always win
+    /// 2. `.env.[mode].local`
+    /// 3. `.env.[mode]`
+    /// 4. `.env.local`
+    /// 5. `.env`
+    ///
+    /// Mode specific files win over the base files, and the git-ignored
+    /// `.local` files win over the other file of the same tier. Unlike
+    /// Next.js, Vite loads `.env.local` in every mode, including `test`.
+    /// Missing files are skipped.
+    #[turbo_tasks::function]
+    pub fn for_vite(dir: FileSystemPathVc, mode: &str, parent: Option<ProcessEnvVc>) -> Self {
+        let files = [
+            format!(".env.{mode}.local"),
+            format!(".env.{mode}"),
+            ".env.local".to_string(),
+        ];
+        let prior = files.iter().fold(parent, |prior, file| {
+            Some(DotenvProcessEnvVc::new(prior, dir.join(file)).into())
+        });
+        Self::new(prior, dir.join(".env"))
     }
 }
 
@@ -36,41 +191,333 @@ impl ProcessEnv for DotenvProcessEnv {
         let empty = IndexMap::new();
         let prior = prior.as_deref().unwrap_or(&empty);
 
-        let file = self.path.read().await?;
-        if let FileContent::Content(f) = &*file {
-            let res;
-            let vars;
-            {
-                let _lock = GLOBAL_ENV_LOCK.lock().unwrap();
-
-                // Unfortunately, dotenvy only looks up variable references from the global env.
-                // So we must mutate while we process. Afterwards, we can restore the initial
-                // state.
-                let initial = env::vars().collect();
+        let options = self.options.await?;
+        let res = match &self.source {
+            DotenvSource::File(path) => {
+                let file = path.read().await?;
+                if let FileContent::Content(f) = &*file {
+                    evaluate(&f.content().to_str()?, prior, &options)
+                } else {
+                    return Ok(EnvMapVc::cell(prior.clone()));
+                }
+            }
+            DotenvSource::String(content) => evaluate(content, prior, &options),
+        };
 
-                restore_env(&initial, prior);
+        match res {
+            Ok(mut vars) => {
+                if let Some(decryptor) = options.decryptor {
+                    for (key, value) in vars.iter_mut() {
+                        if prior.get(key) == Some(value) {
+                            continue;
+                        }
+                        if let Some(encrypted) = value.strip_prefix(&options.secret_marker) {
+                            *value = decryptor.decrypt(encrypted).await?.clone();
+                        }
+                    }
+                }
+                Ok(EnvMapVc::cell(vars))
+            }
+            Err(err) => {
+                let source = match &self.source {
+                    DotenvSource::File(path) => path.to_string().await?.to_string(),
+                    DotenvSource::String(_) => "dotenv string".to_string(),
+                };
+                Err(err).context(anyhow!("unable to read {} for env vars", source))
+            }
+        }
+    }
 
-                // from_read will load parse and evalute the Read, and set variables
-                // into the global env. If a later dotenv defines an already defined
-                // var, it'll be ignored.
-                res = dotenvy::from_read(f.read());
+    #[turbo_tasks::function]
+    async fn read_with_source(
+        self_vc: DotenvProcessEnvVc,
+        name: &str,
+    ) -> Result<OptionSourcedValueVc> {
+        let this = self_vc.await?;
+        // Variables defined by the prior env can't be overridden by the dotenv
+        // file, so the prior is the source whenever it knows the variable.
+        if let Some(prior) = this.prior {
+            let prior_value = prior.read_with_source(name);
+            if prior_value.await?.is_some() {
+                return Ok(prior_value);
+            }
+        }
+        let label = match &this.source {
+            DotenvSource::File(path) => SourceLabel::Dotenv(path.to_string().await?.to_string()),
+            DotenvSource::String(_) => SourceLabel::Unknown,
+        };
+        Ok(OptionSourcedValueVc::cell(
+            self_vc
+                .as_process_env()
+                .read(name)
+                .await?
+                .as_ref()
+                .map(|value| (value.clone(), label)),
+        ))
+    }
+}
 
-                vars = env::vars().collect();
-                restore_env(&vars, &initial);
+#[turbo_tasks::value_impl]
+impl EnvMapVc {
+    /// Serializes the variables into the dotenv format, one `KEY='value'` line
+    /// per variable in the order of the map, which [EnvMapVc::from_dotenv]
+    /// parses back into the same variables. Values containing a `'` or a line
+    /// break are double-quoted and escaped instead. A carriage return can't
+    /// be represented and is an error, as is a name dotenvy can't parse, e.g.
+    /// `ProgramFiles(x86)`.
+    ///
+    /// With `mask_sensitive`, the values of variables which likely hold
+    /// secrets, e.g. `DATABASE_PASSWORD`, are replaced by `********`.
+    #[turbo_tasks::function]
+    pub async fn to_dotenv_string(self, mask_sensitive: bool) -> Result<StringVc> {
+        let mut content = String::new();
+        for (key, value) in &*self.await? {
+            if !is_dotenv_key(key) {
+                return Err(anyhow!(
+                    "env variable {} has a name which can't be written to a dotenv file",
+                    key
+                ));
+            }
+            let value = if mask_sensitive && is_sensitive(key) {
+                MASKED_VALUE
+            } else {
+                value
+            };
+            if value.contains('\r') {
+                return Err(anyhow!(
+                    "env variable {} contains a carriage return, which can't be written to a \
+                     dotenv file",
+                    key
+                ));
+            }
+            if value.contains(['\'', '\n']) {
+                let mut escaped = String::with_capacity(value.len() + 2);
+                for c in value.chars() {
+                    match c {
+                        '\\' | '"' | '$' => {
+                            escaped.push('\\');
+                            escaped.push(c);
+                        }
+                        '\n' => escaped.push_str("\\n"),
+                        _ => escaped.push(c),
+                    }
+                }
+                content.push_str(&format!("{key}=\"{escaped}\"\n"));
+            } else {
+                content.push_str(&format!("{key}='{value}'\n"));
             }
+        }
+        Ok(StringVc::cell(content))
+    }
+
+    /// Parses the variables of a single dotenv file, in the order of the file.
+    /// References to other variables only see the variables defined earlier
+    /// in the same file, not the process env. A missing file has no variables.
+    #[turbo_tasks::function]
+    pub async fn from_dotenv(path: FileSystemPathVc) -> Result<Self> {
+        let file = path.read().await?;
+        let content = match &*file {
+            FileContent::Content(f) => f.content().to_str()?,
+            _ => return Ok(EnvMapVc::empty()),
+        };
+        match evaluate(&content, &IndexMap::new(), &DotenvOptions::default()) {
+            Ok(vars) => Ok(EnvMapVc::cell(vars)),
+            Err(err) => Err(err).context(anyhow!(
+                "unable to read {} for env vars",
+                path.to_string().await?
+            )),
+        }
+    }
+}
+
+/// Evaluates the dotenv `content` on top of the `prior` variables and returns
+/// the resulting variables.
+fn evaluate(
+    content: &str,
+    prior: &IndexMap<String, String>,
+    options: &DotenvOptions,
+) -> Result<IndexMap<String, String>> {
+    let _lock = GLOBAL_ENV_LOCK.lock().unwrap();
+
+    // Unfortunately, dotenvy only looks up variable references from the global env.
+    // So we must mutate while we process. Afterwards, we can restore the initial
+    // state.
+    let initial = env::vars().collect();
 
-            if res.is_err() {
-                res.context(anyhow!(
-                    "unable to read {} for env vars",
-                    self.path.to_string().await?
-                ))?;
+    restore_env(&initial, prior);
+
+    // from_read_iter will parse and evaluate the content line by line. We set the
+    // variables into the global env, so that later lines can reference them. If a
+    // later dotenv defines an already defined var, it'll be ignored.
+    let original = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let content = quote_leading_hashes(original);
+    let content = if options.interpolate {
+        content
+    } else {
+        Cow::Owned(escape_substitutions(&content).into_owned())
+    };
+    // The names as written in the content, by their normalized name.
+    let mut written_keys = IndexMap::new();
+    let res = dotenvy::from_read_iter(content.as_bytes()).try_for_each(|item| {
+        let (written_key, value) = item.map_err(|err| with_line_number(err, &content, original))?;
+        if options.empty_is_unset && value.is_empty() {
+            return Ok(());
+        }
+        let key = options.key_case.apply(written_key.clone());
+        match written_keys.get(&key) {
+            Some(other) if *other != written_key => {
+                return Err(anyhow!(
+                    "{written_key} and {other} are both loaded as {key}",
+                ));
+            }
+            Some(_) => {}
+            None => {
+                written_keys.insert(key.clone(), written_key);
             }
+        }
+        if env::var(&key).is_err() {
+            env::set_var(&key, value);
+        }
+        Ok(())
+    });
 
-            Ok(EnvMapVc::cell(vars))
-        } else {
-            Ok(EnvMapVc::cell(prior.clone()))
+    let vars = env::vars().collect();
+    restore_env(&vars, &initial);
+
+    res.map(|_| vars)
+}
+
+/// Adds the 1-based number and the content of the offending line to a dotenvy
+/// parse error, e.g. ``invalid dotenv syntax at line 7: `not valid` ``.
+/// `content` is `original` after the rewrites of [quote_leading_hashes] and
+/// [escape_substitutions], which keep the lines in place.
+fn with_line_number(err: dotenvy::Error, content: &str, original: &str) -> anyhow::Error {
+    let line = match &err {
+        dotenvy::Error::LineParse(line, _) => line.as_str(),
+        _ => return err.into(),
+    };
+    // dotenvy reports the whole line, or the whole value spanning several
+    // lines, so it starts at the beginning of a line.
+    let start = content
+        .match_indices(line)
+        .map(|(index, _)| index)
+        .find(|&index| index == 0 || content[..index].ends_with('\n'));
+    let number = match start {
+        Some(start) => content[..start].matches('\n').count() + 1,
+        // Errors in substitutions only report the substituted part.
+        None => return err.into(),
+    };
+    let snippet = original.lines().nth(number - 1).unwrap_or_default().trim();
+    anyhow::Error::new(err).context(format!(
+        "invalid dotenv syntax at line {number}: `{snippet}`"
+    ))
+}
+
+/// Whether dotenvy parses `key` as a variable name, i.e. it starts with an
+/// ASCII letter or `_` and continues with ASCII letters, digits, `_` or `.`.
+/// Names like `ProgramFiles(x86)` are valid in the process env but not in a
+/// dotenv file.
+fn is_dotenv_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// dotenvy treats a `#` directly following the `=` as the start of a comment,
+/// but a `#` only starts an inline comment when it's preceded by whitespace.
+/// Quoting that `#` makes dotenvy keep it as part of the value, e.g.
+/// `COLOR=#fff` becomes `COLOR="#"fff`. Lines inside of quoted values and
+/// comments are left untouched.
+fn quote_leading_hashes(content: &str) -> Cow<'_, str> {
+    if !content.contains("=#") {
+        return Cow::Borrowed(content);
+    }
+    let mut quoted = String::with_capacity(content.len() + 2);
+    let mut strong_quote = false;
+    let mut weak_quote = false;
+    let mut backslash = false;
+    let mut comment = false;
+    // Whether the current line hasn't reached its `=` yet, and whether it has
+    // a non-empty key so far.
+    let mut in_key = true;
+    let mut has_key = false;
+    let mut previous = '\n';
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if comment {
+            comment = c != '\n';
+        } else if backslash {
+            backslash = false;
+        } else if strong_quote {
+            strong_quote = c != '\'';
+        } else if c == '\\' {
+            backslash = true;
+        } else if weak_quote {
+            weak_quote = c != '"';
+        } else if c == '\'' {
+            strong_quote = true;
+        } else if c == '"' {
+            weak_quote = true;
+        } else if c == '#' && previous.is_whitespace() {
+            comment = true;
+        } else if c == '=' && in_key && has_key && chars.peek() == Some(&'#') {
+            chars.next();
+            quoted.push_str("=\"#\"");
+            in_key = false;
+            previous = '#';
+            continue;
+        }
+        if c == '\n' && !strong_quote && !weak_quote {
+            in_key = true;
+            has_key = false;
+        } else if in_key {
+            in_key = !matches!(c, '=' | '#' | '"' | '\'');
+            has_key |= !c.is_whitespace();
+        }
+        quoted.push(c);
+        previous = c;
+    }
+    Cow::Owned(quoted)
+}
+
+/// dotenvy substitutes every `$` outside of single-quoted values. Escaping them
+/// as `\$` makes dotenvy keep them literally. Already escaped characters and
+/// comments are left untouched.
+fn escape_substitutions(content: &str) -> Cow<'_, str> {
+    if !content.contains('$') {
+        return Cow::Borrowed(content);
+    }
+    let mut escaped = String::with_capacity(content.len() + 8);
+    let mut strong_quote = false;
+    let mut weak_quote = false;
+    let mut backslash = false;
+    let mut comment = false;
+    let mut previous = '\n';
+    for c in content.chars() {
+        if comment {
+            comment = c != '\n';
+        } else if backslash {
+            backslash = false;
+        } else if strong_quote {
+            strong_quote = c != '\'';
+        } else if c == '$' {
+            escaped.push('\\');
+        } else if c == '\\' {
+            backslash = true;
+        } else if weak_quote {
+            weak_quote = c != '"';
+        } else if c == '\'' {
+            strong_quote = true;
+        } else if c == '"' {
+            weak_quote = true;
+        } else if c == '#' && previous.is_whitespace() {
+            comment = true;
         }
+        escaped.push(c);
+        previous = c;
     }
+    Cow::Owned(escaped)
 }
 
 /// Restores the global env variables to mirror `to`.
@@ -2,14 +2,79 @@ use anyhow::Result;
 use indexmap::IndexMap;
 use turbo_tasks::primitives::OptionStringVc;
 
-use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+use crate::{EnvMapVc, OptionSourcedValueVc, ProcessEnv, ProcessEnvVc};
 
-/// Filters env variables by some prefix. Casing of the env vars is ignored for
-/// filtering.
+/// Decides which env variables are visible through a [FilterProcessEnv].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+enum EnvFilter {
+    /// Variables whose name starts with the prefix.
+    Prefix(String),
+    /// Variables whose name is one of the keys.
+    Keys(Vec<String>),
+    /// Variables whose name matches one of the `allow` patterns, or any name
+    /// when there are none, and none of the `deny` patterns.
+    Rules {
+        allow: Vec<String>,
+        deny: Vec<String>,
+    },
+}
+
+impl EnvFilter {
+    /// Whether the variable `name` is visible. With `case_insensitive`, both
+    /// `name` and the patterns are uppercased before comparing them.
+    fn matches(&self, name: &str, case_insensitive: bool) -> bool {
+        let normalize = |value: &str| {
+            if case_insensitive {
+                value.to_uppercase()
+            } else {
+                value.to_string()
+            }
+        };
+        let name = normalize(name);
+        match self {
+            EnvFilter::Prefix(prefix) => name.starts_with(&normalize(prefix)),
+            EnvFilter::Keys(keys) => keys.iter().any(|key| normalize(key) == name),
+            EnvFilter::Rules { allow, deny } => {
+                let matches = |pattern: &String| glob_matches(&normalize(pattern), &name);
+                (allow.is_empty() || allow.iter().any(matches)) && !deny.iter().any(matches)
+            }
+        }
+    }
+}
+
+/// Matches `name` against a `pattern` in which `*` matches any sequence of
+/// characters, e.g. `NEXT_PUBLIC_*` or `*_SECRET`.
+pub(crate) fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match name.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // The pattern has no `*`.
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Filters env variables by some prefix or by an exact set of names. Casing of
+/// the env vars is ignored for filtering, unless disabled via
+/// [FilterProcessEnvVc::with_case_insensitive].
 #[turbo_tasks::value]
 pub struct FilterProcessEnv {
     prior: ProcessEnvVc,
-    filter: String,
+    filter: EnvFilter,
+    case_insensitive: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -18,10 +83,52 @@ impl FilterProcessEnvVc {
     pub fn new(prior: ProcessEnvVc, filter: String) -> Self {
         FilterProcessEnv {
             prior,
-            filter: filter.to_uppercase(),
+            filter: EnvFilter::Prefix(filter),
+            case_insensitive: true,
         }
         .cell()
     }
+
+    /// Only lets the variables named by `keys` through, every other variable
+    /// is invisible.
+    #[turbo_tasks::function]
+    pub fn allow_keys(prior: ProcessEnvVc, keys: Vec<String>) -> Self {
+        FilterProcessEnv {
+            prior,
+            filter: EnvFilter::Keys(keys),
+            case_insensitive: true,
+        }
+        .cell()
+    }
+
+    /// Only lets the variables through whose name matches one of the `allow`
+    /// patterns and none of the `deny` patterns, so denying wins. An empty
+    /// `allow` list allows every name. A `*` in a pattern matches any
+    /// sequence of characters, e.g. `NEXT_PUBLIC_*`.
+    #[turbo_tasks::function]
+    pub fn with_rules(prior: ProcessEnvVc, allow: Vec<String>, deny: Vec<String>) -> Self {
+        FilterProcessEnv {
+            prior,
+            filter: EnvFilter::Rules { allow, deny },
+            case_insensitive: true,
+        }
+        .cell()
+    }
+
+    /// Returns this filter, ignoring the casing of names and patterns when
+    /// `case_insensitive` is set, which is the default, e.g. so the pattern
+    /// `PATH` also lets `Path` through on Windows. Otherwise they have to match
+    /// exactly.
+    #[turbo_tasks::function]
+    pub async fn with_case_insensitive(self, case_insensitive: bool) -> Result<Self> {
+        let this = self.await?;
+        Ok(FilterProcessEnv {
+            prior: this.prior,
+            filter: this.filter.clone(),
+            case_insensitive,
+        }
+        .cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -31,7 +138,7 @@ impl ProcessEnv for FilterProcessEnv {
         let prior = self.prior.read_all().await?;
         let mut filtered = IndexMap::new();
         for (key, value) in &*prior {
-            if key.to_uppercase().starts_with(&self.filter) {
+            if self.filter.matches(key, self.case_insensitive) {
                 filtered.insert(key.clone(), value.clone());
             }
         }
@@ -40,10 +147,19 @@ impl ProcessEnv for FilterProcessEnv {
 
     #[turbo_tasks::function]
     fn read(&self, name: &str) -> OptionStringVc {
-        if name.to_uppercase().starts_with(&self.filter) {
+        if self.filter.matches(name, self.case_insensitive) {
             self.prior.read(name)
         } else {
             OptionStringVc::cell(None)
         }
     }
+
+    #[turbo_tasks::function]
+    fn read_with_source(&self, name: &str) -> OptionSourcedValueVc {
+        if self.filter.matches(name, self.case_insensitive) {
+            self.prior.read_with_source(name)
+        } else {
+            OptionSourcedValueVc::cell(None)
+        }
+    }
 }
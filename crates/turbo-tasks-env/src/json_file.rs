@@ -0,0 +1,66 @@
+use anyhow::{anyhow, bail, Context, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+use turbo_tasks::ValueToString;
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+
+use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+
+/// Load the environment variables defined as the top-level properties of a
+/// JSON object, on top of an optional prior env. Variables defined by the
+/// file take precedence over the ones defined by the prior env.
+///
+/// Strings are used as is, numbers and booleans are converted to their string
+/// representation. Other values are ignored.
+#[turbo_tasks::value]
+pub struct JsonFileProcessEnv {
+    prior: Option<ProcessEnvVc>,
+    path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl JsonFileProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(prior: Option<ProcessEnvVc>, path: FileSystemPathVc) -> Self {
+        JsonFileProcessEnv { prior, path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for JsonFileProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<EnvMapVc> {
+        let mut vars = if let Some(p) = self.prior {
+            (*p.read_all().await?).clone()
+        } else {
+            IndexMap::new()
+        };
+
+        let file = self.path.read().await?;
+        if let FileContent::Content(f) = &*file {
+            let object = match serde_json::from_str(&f.content().to_str()?) {
+                Ok(Value::Object(object)) => object,
+                Ok(_) => bail!(
+                    "{} must contain a JSON object to be used for env vars",
+                    self.path.to_string().await?
+                ),
+                Err(err) => {
+                    return Err(err).context(anyhow!(
+                        "unable to read {} for env vars",
+                        self.path.to_string().await?
+                    ))
+                }
+            };
+            for (key, value) in object {
+                let value = match value {
+                    Value::String(value) => value,
+                    Value::Number(value) => value.to_string(),
+                    Value::Bool(value) => value.to_string(),
+                    Value::Null | Value::Array(_) | Value::Object(_) => continue,
+                };
+                vars.insert(key, value);
+            }
+        }
+        Ok(EnvMapVc::cell(vars))
+    }
+}
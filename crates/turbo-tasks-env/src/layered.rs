@@ -0,0 +1,56 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::primitives::OptionStringVc;
+
+use crate::{EnvMapVc, OptionSourcedValueVc, ProcessEnv, ProcessEnvVc};
+
+/// Combines several envs into one, where later layers override the variables
+/// of earlier layers, see [ProcessEnv::overlay].
+#[turbo_tasks::value]
+pub struct LayeredProcessEnv {
+    layers: Vec<ProcessEnvVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl LayeredProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(layers: Vec<ProcessEnvVc>) -> Self {
+        LayeredProcessEnv { layers }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for LayeredProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<EnvMapVc> {
+        let mut vars = IndexMap::new();
+        for layer in &self.layers {
+            for (key, value) in &*layer.read_all().await? {
+                vars.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(EnvMapVc::cell(vars))
+    }
+
+    #[turbo_tasks::function]
+    async fn read(&self, name: &str) -> Result<OptionStringVc> {
+        for layer in self.layers.iter().rev() {
+            let value = layer.read(name);
+            if value.await?.is_some() {
+                return Ok(value);
+            }
+        }
+        Ok(OptionStringVc::cell(None))
+    }
+
+    #[turbo_tasks::function]
+    async fn read_with_source(&self, name: &str) -> Result<OptionSourcedValueVc> {
+        for layer in self.layers.iter().rev() {
+            let value = layer.read_with_source(name);
+            if value.await?.is_some() {
+                return Ok(value);
+            }
+        }
+        Ok(OptionSourcedValueVc::cell(None))
+    }
+}
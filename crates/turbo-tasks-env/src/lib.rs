@@ -1,17 +1,36 @@
 #![feature(min_specialization)]
 
 mod command_line;
+mod custom;
 mod dotenv;
 mod filter;
+mod json_file;
+mod layered;
+mod secret;
+mod toml_file;
+mod transform;
 
-use std::{env, sync::Mutex};
+use std::{collections::BTreeMap, env, fmt::Display, path::Path, sync::Mutex, time::Duration};
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use indexmap::IndexMap;
-use turbo_tasks::primitives::OptionStringVc;
+use turbo_tasks::{
+    primitives::{BoolVc, JsonValueVc, OptionStringVc, StringVc, StringsVc, UsizeVc},
+    turbo_tasks, CompletionVc, NothingVc, RawVc,
+};
+use turbo_tasks_fs::{to_sys_path, File, FileContent, FileSystemPathOptionVc, FileSystemPathVc};
 
+use self::filter::glob_matches;
 pub use self::{
-    command_line::CommandLineProcessEnvVc, dotenv::DotenvProcessEnvVc, filter::FilterProcessEnvVc,
+    command_line::CommandLineProcessEnvVc,
+    custom::CustomProcessEnvVc,
+    dotenv::{DotenvOptions, DotenvOptionsVc, DotenvProcessEnvVc, KeyCase},
+    filter::FilterProcessEnvVc,
+    json_file::JsonFileProcessEnvVc,
+    layered::LayeredProcessEnvVc,
+    secret::{SecretDecryptor, SecretDecryptorVc},
+    toml_file::TomlFileProcessEnvVc,
+    transform::{EnvValueTransform, EnvValueTransformVc, LowercaseVc, TrimVc, UppercaseVc},
 };
 
 #[turbo_tasks::value(transparent)]
@@ -23,8 +42,229 @@ impl EnvMapVc {
     pub fn empty() -> Self {
         EnvMap(IndexMap::new()).cell()
     }
+
+    /// Compares the variables of both maps, ignoring their order.
+    #[turbo_tasks::function]
+    pub async fn equals(self, other: EnvMapVc) -> Result<BoolVc> {
+        let this = self.await?;
+        let other = other.await?;
+        Ok(BoolVc::cell(
+            this.len() == other.len()
+                && this
+                    .iter()
+                    .all(|(key, value)| other.get(key) == Some(value)),
+        ))
+    }
+
+    /// Returns the map without the variables named by `keys`. Keys are
+    /// compared exactly and missing keys are ignored.
+    #[turbo_tasks::function]
+    pub async fn without_keys(self, keys: Vec<String>) -> Result<Self> {
+        let this = self.await?;
+        Ok(EnvMapVc::cell(
+            this.iter()
+                .filter(|(key, _)| !keys.contains(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
+    /// Looks up the values of the variables named by `keys`, in the order of
+    /// `keys`. Keys are compared exactly.
+    #[turbo_tasks::function]
+    pub async fn get_many(self, keys: Vec<String>) -> Result<OptionStringsVc> {
+        let this = self.await?;
+        Ok(OptionStringsVc::cell(
+            keys.iter().map(|key| this.get(key).cloned()).collect(),
+        ))
+    }
+
+    /// Fails with an error naming every variable of `keys` that isn't defined
+    /// in this map. Keys are compared exactly.
+    #[turbo_tasks::function]
+    pub async fn require_all(self, keys: Vec<String>) -> Result<NothingVc> {
+        let this = self.await?;
+        let missing = keys
+            .iter()
+            .filter(|key| !this.contains_key(*key))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "missing required env variables: {}",
+                missing.join(", ")
+            ));
+        }
+        Ok(NothingVc::new())
+    }
+
+    /// Returns the variables of this map whose names are also defined in
+    /// `other`, with the values and order of this map.
+    #[turbo_tasks::function]
+    pub async fn intersect(self, other: EnvMapVc) -> Result<Self> {
+        let this = self.await?;
+        let other = other.await?;
+        Ok(EnvMapVc::cell(
+            this.iter()
+                .filter(|(key, _)| other.contains_key(*key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
+    /// Returns this map with the variables of `defaults` it doesn't define
+    /// appended in the order of `defaults`. The values of this map win.
+    #[turbo_tasks::function]
+    pub async fn apply_defaults(self, defaults: EnvMapVc) -> Result<Self> {
+        let mut vars = self.await?.clone_value();
+        for (key, value) in &*defaults.await? {
+            if !vars.contains_key(key) {
+                vars.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(EnvMapVc::cell(vars))
+    }
+
+    /// Counts the variables whose name matches at least one of `patterns`,
+    /// in which `*` matches any sequence of characters, e.g. `NEXT_PUBLIC_*`.
+    /// Names are compared exactly.
+    #[turbo_tasks::function]
+    pub async fn count_matching(self, patterns: Vec<String>) -> Result<UsizeVc> {
+        let this = self.await?;
+        Ok(UsizeVc::cell(
+            this.keys()
+                .filter(|key| patterns.iter().any(|pattern| glob_matches(pattern, key)))
+                .count(),
+        ))
+    }
+
+    /// Returns the map with `prefix` prepended to every key, e.g. `HOST`
+    /// becomes `DB_HOST` for the prefix `DB_`.
+    #[turbo_tasks::function]
+    pub async fn prefixed(self, prefix: &str) -> Result<Self> {
+        let this = self.await?;
+        Ok(EnvMapVc::cell(
+            this.iter()
+                .map(|(key, value)| (format!("{prefix}{key}"), value.clone()))
+                .collect(),
+        ))
+    }
+
+    /// Applies the `transform` to every value of the map.
+    #[turbo_tasks::function]
+    pub async fn map_values(self, transform: EnvValueTransformVc) -> Result<Self> {
+        let this = self.await?;
+        let mut new = IndexMap::with_capacity(this.len());
+        for (key, value) in &*this {
+            let value = transform.transform(key, value).await?;
+            new.insert(key.clone(), (*value).clone());
+        }
+        Ok(EnvMapVc::cell(new))
+    }
+
+    /// Compares this map to `other`. Variables only defined in `other` are
+    /// added, variables only defined in this map are removed. The entries are
+    /// in the order of the map defining them.
+    #[turbo_tasks::function]
+    pub async fn diff(self, other: EnvMapVc) -> Result<EnvDiffVc> {
+        let this = self.await?;
+        let other = other.await?;
+        let mut diff = EnvDiff {
+            added: IndexMap::new(),
+            removed: IndexMap::new(),
+            changed: IndexMap::new(),
+        };
+        for (key, value) in &*this {
+            match other.get(key) {
+                Some(other_value) if other_value != value => {
+                    diff.changed
+                        .insert(key.clone(), (value.clone(), other_value.clone()));
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        for (key, value) in &*other {
+            if !this.contains_key(key) {
+                diff.added.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(diff.cell())
+    }
+
+    /// Returns the variables as a JSON object of string values, in the order
+    /// of the map.
+    #[turbo_tasks::function]
+    pub async fn to_json_object(self) -> Result<JsonValueVc> {
+        let this = self.await?;
+        Ok(JsonValueVc::cell(serde_json::Value::Object(
+            this.iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+                .collect(),
+        )))
+    }
+
+    /// Returns the variables as a pretty-printed JSON object with the keys
+    /// sorted by their bytes, so identical variables always produce identical
+    /// output regardless of the order of the map, e.g. for embedding into a
+    /// hashed build manifest.
+    #[turbo_tasks::function]
+    pub async fn to_sorted_json_string(self) -> Result<StringVc> {
+        let this = self.await?;
+        let sorted = this.iter().collect::<BTreeMap<_, _>>();
+        Ok(StringVc::cell(serde_json::to_string_pretty(&sorted)?))
+    }
+}
+
+/// The differences between two [EnvMap]s, see [EnvMapVc::diff].
+#[turbo_tasks::value]
+pub struct EnvDiff {
+    /// The variables only defined in the other map, with their values.
+    #[turbo_tasks(trace_ignore)]
+    pub added: IndexMap<String, String>,
+    /// The variables only defined in this map, with their values.
+    #[turbo_tasks(trace_ignore)]
+    pub removed: IndexMap<String, String>,
+    /// The variables defined with different values, mapped to the value in
+    /// this map and the value in the other map.
+    #[turbo_tasks(trace_ignore)]
+    pub changed: IndexMap<String, (String, String)>,
+}
+
+/// Identifies the layer of a [ProcessEnv] chain that provided a variable.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum SourceLabel {
+    /// The variable was defined in the env of the current process.
+    CommandLine,
+    /// The variable was defined in the dotenv file at the given path.
+    Dotenv(String),
+    /// The implementation doesn't track where its variables come from.
+    Unknown,
+}
+
+impl Display for SourceLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceLabel::CommandLine => write!(f, "command line"),
+            SourceLabel::Dotenv(path) => write!(f, "{}", path),
+            SourceLabel::Unknown => write!(f, "unknown"),
+        }
+    }
 }
 
+/// The values of several variables, `None` for variables that aren't defined.
+#[turbo_tasks::value(transparent)]
+pub struct OptionStrings(Vec<Option<String>>);
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionSourcedValue(Option<(String, SourceLabel)>);
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionDuration(#[turbo_tasks(trace_ignore)] Option<Duration>);
+
 #[turbo_tasks::value_trait]
 pub trait ProcessEnv {
     // TODO SECURITY: From security perspective it's not good that we read *all* env
@@ -35,6 +275,23 @@ pub trait ProcessEnv {
     /// Reads all env variables into a Map
     fn read_all(&self) -> EnvMapVc;
 
+    /// Reads the env variables whose name starts with `prefix` into a Map.
+    /// Ignores casing.
+    ///
+    /// Implementations that can enumerate their variables cheaply should
+    /// override this, so the variables that don't match never end up in the
+    /// cache. By default, all variables are read and filtered afterwards.
+    async fn read_all_filtered(&self, prefix: &str) -> Result<EnvMapVc> {
+        let prefix = prefix.to_uppercase();
+        let vars = self.read_all().await?;
+        Ok(EnvMapVc::cell(
+            vars.iter()
+                .filter(|(key, _)| key.to_uppercase().starts_with(&prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
     /// Reads a single env variable. Ignores casing.
     async fn read(&self, name: &str) -> Result<OptionStringVc> {
         Ok(OptionStringVc::cell(
@@ -44,6 +301,230 @@ pub trait ProcessEnv {
                 .cloned(),
         ))
     }
+
+    /// Reads a single env variable together with the label of the layer that
+    /// provided it. Ignores casing.
+    async fn read_with_source(&self, name: &str) -> Result<OptionSourcedValueVc> {
+        Ok(OptionSourcedValueVc::cell(
+            self.read(name)
+                .await?
+                .as_ref()
+                .map(|value| (value.clone(), SourceLabel::Unknown)),
+        ))
+    }
+
+    /// Reads a single env variable holding a list of values separated by
+    /// `delimiter`, e.g. `a.com,b.com`. Entries are trimmed and empty entries
+    /// are dropped. Ignores casing.
+    ///
+    /// The delimiter is a string, as a `char` can't be passed to a task.
+    async fn read_list(&self, name: &str, delimiter: &str) -> Result<StringsVc> {
+        Ok(StringsVc::cell(
+            self.read(name)
+                .await?
+                .as_deref()
+                .map(|value| {
+                    value
+                        .split(delimiter)
+                        .map(str::trim)
+                        .filter(|entry| !entry.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Reads a single env variable holding a duration. Ignores casing.
+    ///
+    /// The value is an integer followed by one of the units `ms`, `s`, `m` or
+    /// `h`, e.g. `500ms` or `5m`. A bare integer is a number of seconds. Any
+    /// other value is an error.
+    async fn read_duration(&self, name: &str) -> Result<OptionDurationVc> {
+        let value = self.read(name).await?;
+        let value = match value.as_deref() {
+            Some(value) => value,
+            None => return Ok(OptionDurationVc::cell(None)),
+        };
+        let duration = parse_duration(value.trim()).ok_or_else(|| {
+            anyhow!(
+                "env variable {}={:?} is not a valid duration, expected e.g. 500ms, 30s, 5m or 1h",
+                name,
+                value
+            )
+        })?;
+        Ok(OptionDurationVc::cell(Some(duration)))
+    }
+
+    /// Reads a single env variable holding JSON, e.g. `{"beta":true}`, and
+    /// parses it. An absent variable is JSON `null`, invalid JSON is an error.
+    /// Ignores casing.
+    async fn read_json(&self, name: &str) -> Result<JsonValueVc> {
+        let value = self.read(name).await?;
+        let value = match value.as_deref() {
+            Some(value) => serde_json::from_str(value)
+                .with_context(|| format!("env variable {} is not valid JSON", name))?,
+            None => serde_json::Value::Null,
+        };
+        Ok(JsonValueVc::cell(value))
+    }
+
+    /// Reads a single env variable holding a path. Ignores casing.
+    ///
+    /// Relative paths are resolved relative to `root`. Absolute paths are
+    /// resolved relative to the root of the filesystem of `root`, e.g. the
+    /// directory of a `DiskFileSystem`, and are `None` when they point
+    /// outside of it.
+    async fn read_fs_path(
+        &self,
+        name: &str,
+        root: FileSystemPathVc,
+    ) -> Result<FileSystemPathOptionVc> {
+        let value = self.read(name).await?;
+        let value = match value.as_deref() {
+            Some(value) => value,
+            None => return Ok(FileSystemPathOptionVc::cell(None)),
+        };
+        if !Path::new(value).is_absolute() {
+            return Ok(root.try_join(value));
+        }
+        let fs_root = root.root();
+        match to_sys_path(fs_root).await? {
+            Some(sys_root) => match Path::new(value).strip_prefix(sys_root) {
+                Ok(relative) => {
+                    let relative = relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    Ok(fs_root.try_join(&relative))
+                }
+                Err(_) => Ok(FileSystemPathOptionVc::cell(None)),
+            },
+            None => Ok(fs_root.try_join(value.trim_start_matches('/'))),
+        }
+    }
+
+    /// Writes all variables to the dotenv file at `path`, see
+    /// [EnvMapVc::to_dotenv_string], e.g. to replay the env of a build via
+    /// [EnvMapVc::from_dotenv]. The file contains the real values of secrets
+    /// unless `mask_sensitive` is set.
+    async fn write_dotenv(
+        &self,
+        path: FileSystemPathVc,
+        mask_sensitive: bool,
+    ) -> Result<CompletionVc> {
+        let content = self.read_all().to_dotenv_string(mask_sensitive).await?;
+        Ok(path.write(FileContent::Content(File::from(content)).into()))
+    }
+
+    /// Compares all variables of this env to the variables of `other`, see
+    /// [EnvMapVc::diff].
+    fn diff_against(&self, other: ProcessEnvVc) -> EnvDiffVc {
+        self.read_all().diff(other.read_all())
+    }
+
+    /// Returns an env with the variables of `other` on top of the variables of
+    /// this env, so `other` wins when both define a variable.
+    fn overlay(&self, other: ProcessEnvVc) -> ProcessEnvVc {
+        LayeredProcessEnvVc::new(vec![*self, other]).into()
+    }
+
+    /// Returns an env that only lets the variables through whose name matches
+    /// one of `patterns`, see [FilterProcessEnvVc::with_rules].
+    fn filtered(&self, patterns: Vec<String>) -> ProcessEnvVc {
+        FilterProcessEnvVc::with_rules(*self, patterns, vec![]).into()
+    }
+
+    /// Returns a flat env holding a copy of the variables of this env. Reads
+    /// from the snapshot are consistent, even when the variables of this env
+    /// change afterwards.
+    async fn snapshot(&self) -> Result<ProcessEnvVc> {
+        // INVALIDATION: The variables are read untracked, so the snapshot is
+        // intentionally not recomputed when they change.
+        let vars = RawVc::from(self.read_all())
+            .into_read_untracked::<EnvMap>(&*turbo_tasks())
+            .await?;
+        Ok(CustomProcessEnvVc::new(EnvMapVc::cell(vars.0.clone())).into())
+    }
+
+    /// Formats all variables as `KEY = value` lines for logging, sorted by
+    /// name and aligned at the `=`. The values of variables whose names look
+    /// like they hold secrets, e.g. `API_TOKEN`, are masked.
+    async fn pretty(&self) -> Result<StringVc> {
+        let vars = self.read_all().await?;
+        let mut vars = vars.iter().collect::<Vec<_>>();
+        vars.sort_by_key(|(key, _)| *key);
+        let width = vars
+            .iter()
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or_default();
+        let mut pretty = String::new();
+        for (key, value) in vars {
+            let value = if is_sensitive(key) {
+                MASKED_VALUE
+            } else {
+                value
+            };
+            pretty.push_str(&format!("{key:width$} = {value}\n"));
+        }
+        Ok(StringVc::cell(pretty))
+    }
+}
+
+impl ProcessEnvVc {
+    /// Reads all env variables as owned pairs, e.g. for
+    /// [std::process::Command::envs].
+    ///
+    /// This materializes the values of *all* variables outside of turbo-tasks,
+    /// including secrets. Only pass them to processes that are trusted with the
+    /// whole env and don't log or persist them.
+    pub async fn as_command_env(self) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .read_all()
+            .await?
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// Replaces the values of secret variables in [ProcessEnv::pretty] and
+/// [EnvMapVc::to_dotenv_string].
+pub(crate) const MASKED_VALUE: &str = "********";
+
+/// Name prefixes of variables which likely hold secrets, compared uppercased.
+const SENSITIVE_PREFIXES: [&str; 2] = ["AWS_", "NPM_"];
+
+/// Name suffixes of variables which likely hold secrets, compared uppercased.
+const SENSITIVE_SUFFIXES: [&str; 4] = ["_SECRET", "_TOKEN", "_KEY", "_PASSWORD"];
+
+/// Whether the variable `name` likely holds a secret, e.g. `DATABASE_PASSWORD`
+/// or `AWS_ACCESS_KEY_ID`.
+pub(crate) fn is_sensitive(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SENSITIVE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        || SENSITIVE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number = number.parse::<u64>().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(number)),
+        "s" => Some(Duration::from_secs(number)),
+        "m" => Some(Duration::from_secs(number.checked_mul(60)?)),
+        "h" => Some(Duration::from_secs(number.checked_mul(60 * 60)?)),
+        _ => None,
+    }
 }
 
 #[turbo_tasks::function]
@@ -0,0 +1,9 @@
+use turbo_tasks::primitives::StringVc;
+
+/// Decrypts the values of a dotenv file that are marked as encrypted, see
+/// [crate::DotenvOptions::decryptor].
+#[turbo_tasks::value_trait]
+pub trait SecretDecryptor {
+    /// Decrypts `value`, which doesn't include the marker anymore.
+    fn decrypt(&self, value: &str) -> StringVc;
+}
@@ -0,0 +1,64 @@
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use toml::{value::Table, Value};
+use turbo_tasks::ValueToString;
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+
+use crate::{EnvMapVc, ProcessEnv, ProcessEnvVc};
+
+/// Load the environment variables defined as the top-level keys of a TOML
+/// file, on top of an optional prior env. Variables defined by the file take
+/// precedence over the ones defined by the prior env.
+///
+/// Strings are used as is, numbers, booleans and datetimes are converted to
+/// their string representation. Arrays and tables are ignored.
+#[turbo_tasks::value]
+pub struct TomlFileProcessEnv {
+    prior: Option<ProcessEnvVc>,
+    path: FileSystemPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl TomlFileProcessEnvVc {
+    #[turbo_tasks::function]
+    pub fn new(prior: Option<ProcessEnvVc>, path: FileSystemPathVc) -> Self {
+        TomlFileProcessEnv { prior, path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for TomlFileProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<EnvMapVc> {
+        let mut vars = if let Some(p) = self.prior {
+            (*p.read_all().await?).clone()
+        } else {
+            IndexMap::new()
+        };
+
+        let file = self.path.read().await?;
+        if let FileContent::Content(f) = &*file {
+            let table = match toml::from_str::<Table>(&f.content().to_str()?) {
+                Ok(table) => table,
+                Err(err) => {
+                    return Err(err).context(anyhow!(
+                        "unable to read {} for env vars",
+                        self.path.to_string().await?
+                    ))
+                }
+            };
+            for (key, value) in table {
+                let value = match value {
+                    Value::String(value) => value,
+                    Value::Integer(value) => value.to_string(),
+                    Value::Float(value) => value.to_string(),
+                    Value::Boolean(value) => value.to_string(),
+                    Value::Datetime(value) => value.to_string(),
+                    Value::Array(_) | Value::Table(_) => continue,
+                };
+                vars.insert(key, value);
+            }
+        }
+        Ok(EnvMapVc::cell(vars))
+    }
+}
@@ -0,0 +1,68 @@
+use turbo_tasks::primitives::StringVc;
+
+/// Transforms the value of an env variable, e.g. for
+/// [crate::EnvMapVc::map_values].
+#[turbo_tasks::value_trait]
+pub trait EnvValueTransform {
+    fn transform(&self, key: &str, value: &str) -> StringVc;
+}
+
+/// Removes leading and trailing whitespace from values.
+#[turbo_tasks::value]
+pub struct Trim;
+
+#[turbo_tasks::value_impl]
+impl TrimVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Trim.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EnvValueTransform for Trim {
+    #[turbo_tasks::function]
+    fn transform(&self, _key: &str, value: &str) -> StringVc {
+        StringVc::cell(value.trim().to_string())
+    }
+}
+
+/// Converts values to lowercase.
+#[turbo_tasks::value]
+pub struct Lowercase;
+
+#[turbo_tasks::value_impl]
+impl LowercaseVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Lowercase.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EnvValueTransform for Lowercase {
+    #[turbo_tasks::function]
+    fn transform(&self, _key: &str, value: &str) -> StringVc {
+        StringVc::cell(value.to_lowercase())
+    }
+}
+
+/// Converts values to uppercase.
+#[turbo_tasks::value]
+pub struct Uppercase;
+
+#[turbo_tasks::value_impl]
+impl UppercaseVc {
+    #[turbo_tasks::function]
+    pub fn new() -> Self {
+        Uppercase.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EnvValueTransform for Uppercase {
+    #[turbo_tasks::function]
+    fn transform(&self, _key: &str, value: &str) -> StringVc {
+        StringVc::cell(value.to_uppercase())
+    }
+}
@@ -0,0 +1,45 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use common::env_map;
+use turbo_tasks_env::{CustomProcessEnvVc, DotenvProcessEnvVc, ProcessEnvVc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn overlay_and_filtered_chain() {
+    turbo_tasks_env::register();
+    run! {
+        let base: ProcessEnvVc = CustomProcessEnvVc::new(env_map(&[
+            ("NEXT_PUBLIC_URL", "https://base"),
+            ("NEXT_PUBLIC_NAME", "base"),
+            ("SECRET", "hunter2"),
+        ]))
+        .into();
+        let dotenv = DotenvProcessEnvVc::from_string(
+            "NEXT_PUBLIC_URL=https://dotenv\nNEXT_PUBLIC_DEBUG=1\n".to_string(),
+            None,
+        );
+
+        let env = base
+            .overlay(dotenv.into())
+            .filtered(vec!["NEXT_PUBLIC_*".to_string()]);
+
+        assert_eq!(env.read("NEXT_PUBLIC_URL").await?.as_deref(), Some("https://dotenv"));
+        assert_eq!(env.read("NEXT_PUBLIC_NAME").await?.as_deref(), Some("base"));
+        assert_eq!(env.read("SECRET").await?.as_deref(), None);
+        let all = env.read_all().await?;
+        assert_eq!(
+            all.iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("NEXT_PUBLIC_URL", "https://dotenv"),
+                ("NEXT_PUBLIC_NAME", "base"),
+                ("NEXT_PUBLIC_DEBUG", "1"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#![feature(min_specialization)]
+
+use std::{ffi::OsStr, process::Command};
+
+mod common;
+
+use common::env_map;
+use indexmap::IndexMap;
+use turbo_tasks_env::{CustomProcessEnvVc, ProcessEnvVc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn command_env_round_trips_through_command() {
+    turbo_tasks_env::register();
+    run! {
+        let env: ProcessEnvVc =
+            CustomProcessEnvVc::new(env_map(&[("FOO", "foo"), ("BAR", "bar baz"), ("EMPTY", "")]))
+                .into();
+
+        let pairs = env.as_command_env().await?;
+        assert_eq!(
+            pairs,
+            vec![
+                ("FOO".to_string(), "foo".to_string()),
+                ("BAR".to_string(), "bar baz".to_string()),
+                ("EMPTY".to_string(), "".to_string()),
+            ]
+        );
+
+        let mut command = Command::new("env");
+        command.env_clear().envs(pairs);
+        let command_env = command
+            .get_envs()
+            .map(|(key, value)| (key, value.unwrap_or_default()))
+            .collect::<IndexMap<_, _>>();
+        assert_eq!(command_env.len(), 3);
+        assert_eq!(command_env.get(OsStr::new("FOO")), Some(&OsStr::new("foo")));
+        assert_eq!(command_env.get(OsStr::new("BAR")), Some(&OsStr::new("bar baz")));
+        assert_eq!(command_env.get(OsStr::new("EMPTY")), Some(&OsStr::new("")));
+    }
+}
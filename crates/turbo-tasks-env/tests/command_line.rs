@@ -0,0 +1,35 @@
+#![feature(min_specialization)]
+
+use std::env;
+
+use turbo_tasks_env::{CommandLineProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn sensitive_variables_can_be_excluded_from_read_all() {
+    turbo_tasks_env::register();
+    env::set_var("COMMAND_LINE_TEST_DATABASE_PASSWORD", "hunter2");
+    env::set_var("COMMAND_LINE_TEST_DATABASE_HOST", "localhost");
+    run! {
+        let env = CommandLineProcessEnvVc::with_options(true);
+        let vars = env.read_all().await?;
+        assert!(!vars.contains_key("COMMAND_LINE_TEST_DATABASE_PASSWORD"));
+        assert_eq!(
+            vars.get("COMMAND_LINE_TEST_DATABASE_HOST").map(String::as_str),
+            Some("localhost")
+        );
+        assert_eq!(
+            env.read("COMMAND_LINE_TEST_DATABASE_PASSWORD").await?.as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(
+            env.read("command_line_test_database_host").await?.as_deref(),
+            Some("localhost")
+        );
+
+        let env = CommandLineProcessEnvVc::new();
+        assert!(env.read_all().await?.contains_key("COMMAND_LINE_TEST_DATABASE_PASSWORD"));
+    }
+}
@@ -0,0 +1,32 @@
+//! Fixtures shared by the integration tests. Each test file only uses some of
+//! them.
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use turbo_tasks_env::EnvMapVc;
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystemPathVc, FileSystemVc};
+
+pub fn register_deps() {
+    turbo_tasks_fs::register();
+    turbo_tasks_env::register();
+}
+
+pub fn disk_fs(dir: &Path) -> DiskFileSystemVc {
+    DiskFileSystemVc::new("test".to_string(), dir.to_str().unwrap().to_string())
+}
+
+pub fn disk_root(dir: &Path) -> FileSystemPathVc {
+    FileSystemVc::from(disk_fs(dir)).root()
+}
+
+pub fn vars(vars: &[(&str, &str)]) -> IndexMap<String, String> {
+    vars.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+pub fn env_map(vars: &[(&str, &str)]) -> EnvMapVc {
+    EnvMapVc::cell(self::vars(vars))
+}
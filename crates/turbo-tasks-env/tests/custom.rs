@@ -0,0 +1,54 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::env;
+
+use common::env_map;
+use turbo_tasks_env::{CommandLineProcessEnvVc, CustomProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn over_overrides_variables_of_the_parent() {
+    turbo_tasks_env::register();
+    env::set_var("CUSTOM_TEST_NODE_ENV", "development");
+    env::set_var("CUSTOM_TEST_HOST", "localhost");
+    run! {
+        let env = CustomProcessEnvVc::over(
+            CommandLineProcessEnvVc::new().into(),
+            &[("CUSTOM_TEST_NODE_ENV", "production")],
+        );
+
+        assert_eq!(env.read("CUSTOM_TEST_NODE_ENV").await?.as_deref(), Some("production"));
+        assert_eq!(env.read("CUSTOM_TEST_HOST").await?.as_deref(), Some("localhost"));
+        let all = env.read_all().await?;
+        assert_eq!(all.get("CUSTOM_TEST_NODE_ENV").map(String::as_str), Some("production"));
+        assert_eq!(all.get("CUSTOM_TEST_HOST").map(String::as_str), Some("localhost"));
+    }
+}
+
+#[tokio::test]
+async fn over_shadows_the_parent_and_falls_back_to_it() {
+    turbo_tasks_env::register();
+    run! {
+        let parent = CustomProcessEnvVc::new(env_map(&[
+            ("NODE_ENV", "development"),
+            ("HOST", "localhost"),
+        ]));
+        let env = CustomProcessEnvVc::over(parent.into(), &[("NODE_ENV", "production")]);
+
+        assert_eq!(env.read("NODE_ENV").await?.as_deref(), Some("production"));
+        assert_eq!(env.read("HOST").await?.as_deref(), Some("localhost"));
+        assert_eq!(env.read("PORT").await?.as_deref(), None);
+        assert_eq!(
+            parent.read("NODE_ENV").await?.as_deref(),
+            Some("development")
+        );
+        let all = env.read_all().await?;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all.get("NODE_ENV").map(String::as_str), Some("production"));
+        assert_eq!(all.get("HOST").map(String::as_str), Some("localhost"));
+    }
+}
@@ -0,0 +1,45 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use common::env_map;
+use turbo_tasks_env::{CustomProcessEnvVc, DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn diff_against_compares_different_implementations() {
+    turbo_tasks_env::register();
+    run! {
+        let custom = CustomProcessEnvVc::new(env_map(&[
+            ("HOST", "localhost"),
+            ("PORT", "3000"),
+            ("DEBUG", "1"),
+        ]));
+        let dotenv = DotenvProcessEnvVc::from_string(
+            "HOST=localhost\nPORT=8080\nUSER=admin\n".to_string(),
+            None,
+        );
+
+        let diff = custom.diff_against(dotenv.into()).await?;
+        assert_eq!(
+            diff.added.iter().collect::<Vec<_>>(),
+            [(&"USER".to_string(), &"admin".to_string())]
+        );
+        assert_eq!(
+            diff.removed.iter().collect::<Vec<_>>(),
+            [(&"DEBUG".to_string(), &"1".to_string())]
+        );
+        assert_eq!(
+            diff.changed.iter().collect::<Vec<_>>(),
+            [(
+                &"PORT".to_string(),
+                &("3000".to_string(), "8080".to_string())
+            )]
+        );
+
+        let same = custom.diff_against(custom.into()).await?;
+        assert!(same.added.is_empty() && same.removed.is_empty() && same.changed.is_empty());
+    }
+}
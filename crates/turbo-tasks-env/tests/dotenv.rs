@@ -0,0 +1,448 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::fs;
+
+use anyhow::Result;
+use common::{disk_fs, disk_root, env_map, register_deps};
+use turbo_tasks::primitives::StringVc;
+use turbo_tasks_env::{
+    CustomProcessEnvVc, DotenvOptions, DotenvProcessEnvVc, EnvMapVc, KeyCase, ProcessEnv,
+    SecretDecryptor, SecretDecryptorVc, SourceLabel,
+};
+use turbo_tasks_fs::FileSystemVc;
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_with_source_reports_overriding_layer() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env.local"), "FOO=local\n")?;
+        fs::write(dir.path().join(".env"), "FOO=base\nBAR=base\n")?;
+        let root = disk_root(dir.path());
+        let local = DotenvProcessEnvVc::new(None, root.join(".env.local"));
+        let env = DotenvProcessEnvVc::new(Some(local.into()), root.join(".env"));
+
+        let foo = env.read_with_source("FOO").await?;
+        let (value, source) = foo.as_ref().unwrap();
+        assert_eq!(value, "local");
+        assert!(matches!(source, SourceLabel::Dotenv(path) if path.ends_with(".env.local")));
+
+        let bar = env.read_with_source("BAR").await?;
+        let (value, source) = bar.as_ref().unwrap();
+        assert_eq!(value, "base");
+        assert!(matches!(source, SourceLabel::Dotenv(path) if path.ends_with(".env")));
+
+        assert!(env.read_with_source("MISSING").await?.is_none());
+    }
+}
+
+#[tokio::test]
+async fn empty_values_are_kept_by_default() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "FOO=\nBAR=bar\n")?;
+        let env = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+
+        assert_eq!(env.read("FOO").await?.as_deref(), Some(""));
+        assert_eq!(env.read_all().await?.get("FOO").map(String::as_str), Some(""));
+    }
+}
+
+#[tokio::test]
+async fn empty_values_can_be_treated_as_unset() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "FOO=\nBAR=bar\n")?;
+        let options = DotenvOptions {
+            empty_is_unset: true,
+            ..Default::default()
+        };
+        let env = DotenvProcessEnvVc::with_options(
+            None,
+            disk_root(dir.path()).join(".env"),
+            options.cell(),
+        );
+
+        assert_eq!(env.read("FOO").await?.as_deref(), None);
+        assert_eq!(env.read("BAR").await?.as_deref(), Some("bar"));
+        assert!(!env.read_all().await?.contains_key("FOO"));
+    }
+}
+
+#[tokio::test]
+async fn inline_comments_are_trimmed_from_unquoted_values() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".env"),
+            "PORT=3000 # the port\nCOLOR=#fff\nURL=http://x#frag\nQ=\"a # b\"\n",
+        )?;
+        let env = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+
+        assert_eq!(env.read("PORT").await?.as_deref(), Some("3000"));
+        assert_eq!(env.read("COLOR").await?.as_deref(), Some("#fff"));
+        assert_eq!(env.read("URL").await?.as_deref(), Some("http://x#frag"));
+        assert_eq!(env.read("Q").await?.as_deref(), Some("a # b"));
+    }
+}
+
+#[tokio::test]
+async fn leading_hashes_in_multiline_values_are_kept() {
+    register_deps();
+    run! {
+        let env = DotenvProcessEnvVc::from_string(
+            "A=\"x=#y\nb=#c\"\nB='x=#y\nb=#c'\nC=#d\n".to_string(),
+            None,
+        );
+
+        assert_eq!(env.read("A").await?.as_deref(), Some("x=#y\nb=#c"));
+        assert_eq!(env.read("B").await?.as_deref(), Some("x=#y\nb=#c"));
+        assert_eq!(env.read("C").await?.as_deref(), Some("#d"));
+    }
+}
+
+#[tokio::test]
+async fn whitespace_around_keys_and_unquoted_values_is_trimmed() {
+    register_deps();
+    run! {
+        let content = "  FOO = bar  \nBAR=  bar  \n\tBAZ\t=\tbaz\t\nQUOTED=\"  bar  \"\nSINGLE = '  bar  '\nCOMMENT = bar  # trailing\n";
+        let env = DotenvProcessEnvVc::from_string(content.to_string(), None);
+
+        assert_eq!(env.read("FOO").await?.as_deref(), Some("bar"));
+        assert_eq!(env.read("BAR").await?.as_deref(), Some("bar"));
+        assert_eq!(env.read("BAZ").await?.as_deref(), Some("baz"));
+        assert_eq!(env.read("QUOTED").await?.as_deref(), Some("  bar  "));
+        assert_eq!(env.read("SINGLE").await?.as_deref(), Some("  bar  "));
+        assert_eq!(env.read("COMMENT").await?.as_deref(), Some("bar"));
+        assert_eq!(
+            env.read_all().await?.keys().collect::<Vec<_>>(),
+            ["FOO", "BAR", "BAZ", "QUOTED", "SINGLE", "COMMENT"]
+        );
+
+        let env = DotenvProcessEnvVc::from_string("FOO=bar baz\n".to_string(), None);
+        assert!(env.read_all().await.is_err());
+    }
+}
+
+#[tokio::test]
+async fn syntax_errors_name_the_line() {
+    register_deps();
+    run! {
+        let content = "# settings\nHOST=localhost\n\nCERT=\"-----BEGIN-----\nabc\n-----END-----\"\nthis is not valid\nPORT=5432\n";
+        let env = DotenvProcessEnvVc::from_string(content.to_string(), None);
+
+        let err = env.read_all().await.unwrap_err();
+        assert!(format!("{err:#}").contains("invalid dotenv syntax at line 7: `this is not valid`"));
+    }
+}
+
+#[tokio::test]
+async fn from_string_evaluates_content() {
+    register_deps();
+    run! {
+        let content = r#"# database settings
+HOST=localhost
+PORT=5432 # the default port
+URL="postgres://${HOST}:${PORT}/db"
+GREETING='hello # world'
+"#;
+        let env = DotenvProcessEnvVc::from_string(content.to_string(), None);
+
+        assert_eq!(env.read("HOST").await?.as_deref(), Some("localhost"));
+        assert_eq!(env.read("PORT").await?.as_deref(), Some("5432"));
+        assert_eq!(
+            env.read("URL").await?.as_deref(),
+            Some("postgres://localhost:5432/db")
+        );
+        assert_eq!(env.read("GREETING").await?.as_deref(), Some("hello # world"));
+        assert_eq!(env.read_all().await?.len(), 4);
+    }
+}
+
+#[tokio::test]
+async fn for_vite_follows_vite_precedence() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".env"),
+            "BASE=base\nMODE=base\nLOCAL=base\nMODE_LOCAL=base\n",
+        )?;
+        fs::write(dir.path().join(".env.local"), "LOCAL=local\nMODE_LOCAL=local\n")?;
+        fs::write(dir.path().join(".env.staging"), "MODE=mode\nMODE_LOCAL=mode\n")?;
+        fs::write(dir.path().join(".env.staging.local"), "MODE_LOCAL=mode.local\n")?;
+        fs::write(dir.path().join(".env.production"), "MODE=production\n")?;
+        let env = DotenvProcessEnvVc::for_vite(disk_root(dir.path()), "staging", None);
+
+        assert_eq!(env.read("BASE").await?.as_deref(), Some("base"));
+        assert_eq!(env.read("MODE").await?.as_deref(), Some("mode"));
+        assert_eq!(env.read("LOCAL").await?.as_deref(), Some("local"));
+        assert_eq!(env.read("MODE_LOCAL").await?.as_deref(), Some("mode.local"));
+
+        // `.env.local` is loaded in test mode too, and missing mode files are skipped.
+        let env = DotenvProcessEnvVc::for_vite(disk_root(dir.path()), "test", None);
+        assert_eq!(env.read("LOCAL").await?.as_deref(), Some("local"));
+        assert_eq!(env.read("MODE").await?.as_deref(), Some("base"));
+    }
+}
+
+#[tokio::test]
+async fn for_vite_keeps_parent_variables() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env.staging.local"), "FOO=local\n")?;
+        let parent = DotenvProcessEnvVc::from_string("FOO=parent\n".to_string(), None);
+        let env =
+            DotenvProcessEnvVc::for_vite(disk_root(dir.path()), "staging", Some(parent.into()));
+
+        assert_eq!(env.read("FOO").await?.as_deref(), Some("parent"));
+    }
+}
+
+#[tokio::test]
+async fn from_dotenv_parses_a_single_file() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".env"),
+            "ZED=z\nALPHA=a # comment\nURL=\"http://${ZED}\"\nHOME_DIR=${HOME}\n",
+        )?;
+        let map = EnvMapVc::from_dotenv(disk_root(dir.path()).join(".env")).await?;
+
+        assert_eq!(
+            map.iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            [("ZED", "z"), ("ALPHA", "a"), ("URL", "http://z"), ("HOME_DIR", "")]
+        );
+        let missing = EnvMapVc::from_dotenv(disk_root(dir.path()).join(".env.missing")).await?;
+        assert!(missing.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn write_dotenv_round_trips_through_from_dotenv() {
+    register_deps();
+    run! {
+        let vars = [
+            ("PLAIN", "value"),
+            ("SPACES", "  padded value  "),
+            ("EMPTY", ""),
+            ("COLOR", "#fff"),
+            ("COMMENT", "a # b"),
+            ("DOLLAR", "$HOME and ${HOME}"),
+            ("QUOTES", "it's \"quoted\""),
+            ("MULTILINE", "first\nsecond $x \\ third"),
+            ("API_TOKEN", "hunter2"),
+            ("_dotted.name_2", "value"),
+        ];
+        let env = CustomProcessEnvVc::over(CustomProcessEnvVc::new(EnvMapVc::empty()).into(), &vars);
+        let dir = tempfile::tempdir()?;
+        let disk_fs = disk_fs(dir.path());
+        let root = FileSystemVc::from(disk_fs).root();
+
+        env.write_dotenv(root.join(".env.replay"), false).await?;
+        // The write only invalidates the reads via the file watcher.
+        disk_fs.await?.invalidate();
+        let map = EnvMapVc::from_dotenv(root.join(".env.replay"))
+            .strongly_consistent()
+            .await?;
+        assert_eq!(
+            map.iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            vars
+        );
+
+        env.write_dotenv(root.join(".env.masked"), true).await?;
+        let masked = fs::read_to_string(dir.path().join(".env.masked"))?;
+        assert!(masked.contains("API_TOKEN='********'\n"));
+        assert!(!masked.contains("hunter2"));
+    }
+}
+
+#[tokio::test]
+async fn write_dotenv_rejects_names_dotenvy_cant_parse() {
+    register_deps();
+    run! {
+        for key in ["ProgramFiles(x86)", "BASH_FUNC_x%%", "my-var", "1ST"] {
+            let map = env_map(&[("PLAIN", "value"), (key, "value")]);
+            let err = map.to_dotenv_string(false).await.unwrap_err();
+            assert!(
+                format!("{err:#}").contains(&format!(
+                    "env variable {key} has a name which can't be written to a dotenv file"
+                )),
+                "{err:#}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn changes_to_the_file_are_picked_up_after_invalidation() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        let env_file = dir.path().join(".env");
+        fs::write(&env_file, "FOO=before\n")?;
+        let disk_fs = disk_fs(dir.path());
+        let root = FileSystemVc::from(disk_fs).root();
+        let env = DotenvProcessEnvVc::new(None, root.join(".env"));
+
+        assert_eq!(env.read("FOO").strongly_consistent().await?.as_deref(), Some("before"));
+
+        fs::write(&env_file, "FOO=after\n")?;
+        disk_fs.await?.invalidate();
+
+        assert_eq!(env.read("FOO").strongly_consistent().await?.as_deref(), Some("after"));
+    }
+}
+
+#[tokio::test]
+async fn keys_can_be_uppercased() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "db_host=localhost\nDB_PORT=5432\n")?;
+        let options = DotenvOptions {
+            key_case: KeyCase::Upper,
+            ..Default::default()
+        };
+        let env = DotenvProcessEnvVc::with_options(
+            None,
+            disk_root(dir.path()).join(".env"),
+            options.cell(),
+        );
+
+        let vars = env.read_all().await?;
+        assert_eq!(vars.get("DB_HOST").map(String::as_str), Some("localhost"));
+        assert_eq!(vars.get("DB_PORT").map(String::as_str), Some("5432"));
+        assert!(!vars.contains_key("db_host"));
+    }
+}
+
+#[tokio::test]
+async fn keys_colliding_after_normalization_are_an_error() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "Path=/a\nPATH=/b\n")?;
+        let options = DotenvOptions {
+            key_case: KeyCase::Upper,
+            ..Default::default()
+        };
+        let env = DotenvProcessEnvVc::with_options(
+            None,
+            disk_root(dir.path()).join(".env"),
+            options.cell(),
+        );
+
+        let err = env.read_all().await.unwrap_err();
+        assert!(format!("{err:#}").contains("PATH and Path are both loaded as PATH"));
+    }
+}
+
+#[tokio::test]
+async fn snapshot_is_unchanged_after_invalidation() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        let env_file = dir.path().join(".env");
+        fs::write(&env_file, "FOO=before\n")?;
+        let disk_fs = disk_fs(dir.path());
+        let root = FileSystemVc::from(disk_fs).root();
+        let env = DotenvProcessEnvVc::new(None, root.join(".env"));
+        let snapshot = env.snapshot();
+
+        assert_eq!(snapshot.read("FOO").strongly_consistent().await?.as_deref(), Some("before"));
+
+        fs::write(&env_file, "FOO=after\n")?;
+        disk_fs.await?.invalidate();
+
+        assert_eq!(env.read("FOO").strongly_consistent().await?.as_deref(), Some("after"));
+        assert_eq!(snapshot.read("FOO").strongly_consistent().await?.as_deref(), Some("before"));
+    }
+}
+
+#[tokio::test]
+async fn references_stay_literal_without_interpolation() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".env"),
+            "HOST=localhost\nTEMPLATE=${NOT_EXPANDED}\nURL=\"http://${HOST}:${PORT}\"\nSINGLE='${HOST}'\nESCAPED=\\$HOST # don't expand\n",
+        )?;
+        let options = DotenvOptions {
+            interpolate: false,
+            ..Default::default()
+        };
+        let env = DotenvProcessEnvVc::with_options(
+            None,
+            disk_root(dir.path()).join(".env"),
+            options.cell(),
+        );
+
+        assert_eq!(env.read("TEMPLATE").await?.as_deref(), Some("${NOT_EXPANDED}"));
+        assert_eq!(env.read("URL").await?.as_deref(), Some("http://${HOST}:${PORT}"));
+        assert_eq!(env.read("SINGLE").await?.as_deref(), Some("${HOST}"));
+        assert_eq!(env.read("ESCAPED").await?.as_deref(), Some("$HOST"));
+
+        let env = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+        assert_eq!(env.read("URL").await?.as_deref(), Some("http://localhost:"));
+    }
+}
+
+/// Decrypts hex encoded values XOR-ed with `key`.
+#[turbo_tasks::value]
+struct XorDecryptor {
+    key: u8,
+}
+
+#[turbo_tasks::value_impl]
+impl SecretDecryptor for XorDecryptor {
+    #[turbo_tasks::function]
+    fn decrypt(&self, value: &str) -> Result<StringVc> {
+        let bytes = (0..value.len())
+            .step_by(2)
+            .map(|i| Ok(u8::from_str_radix(&value[i..i + 2], 16)? ^ self.key))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(StringVc::cell(String::from_utf8(bytes)?))
+    }
+}
+
+#[tokio::test]
+async fn marked_values_are_decrypted() {
+    register_deps();
+    run! {
+        let encrypted = "hunter2"
+            .bytes()
+            .map(|b| format!("{:02x}", b ^ 0x2a))
+            .collect::<String>();
+        let content = format!("PASSWORD=enc:{encrypted}\nUSER=admin\n");
+        let options = DotenvOptions {
+            decryptor: Some(XorDecryptor { key: 0x2a }.cell().into()),
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), content)?;
+        let env = DotenvProcessEnvVc::with_options(
+            None,
+            disk_root(dir.path()).join(".env"),
+            options.cell(),
+        );
+
+        assert_eq!(env.read("PASSWORD").await?.as_deref(), Some("hunter2"));
+        assert_eq!(env.read("USER").await?.as_deref(), Some("admin"));
+    }
+}
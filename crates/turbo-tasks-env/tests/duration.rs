@@ -0,0 +1,25 @@
+#![feature(min_specialization)]
+
+use std::time::Duration;
+
+use turbo_tasks_env::{DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_duration_parses_units() {
+    turbo_tasks_env::register();
+    run! {
+        let env = DotenvProcessEnvVc::from_string(
+            "POLL=500ms\nCACHE_TTL=2m\nTIMEOUT=10\nRETRY=soon\n".to_string(),
+            None,
+        );
+
+        assert_eq!(*env.read_duration("POLL").await?, Some(Duration::from_millis(500)));
+        assert_eq!(*env.read_duration("CACHE_TTL").await?, Some(Duration::from_secs(120)));
+        assert_eq!(*env.read_duration("TIMEOUT").await?, Some(Duration::from_secs(10)));
+        assert_eq!(*env.read_duration("MISSING").await?, None);
+        assert!(env.read_duration("RETRY").await.is_err());
+    }
+}
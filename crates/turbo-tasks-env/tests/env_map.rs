@@ -0,0 +1,210 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use common::env_map;
+use turbo_tasks_env::TrimVc;
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn equals_ignores_order() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("FOO", "foo"), ("BAR", "bar")]);
+        let b = env_map(&[("BAR", "bar"), ("FOO", "foo")]);
+        assert!(*a.equals(b).await?);
+    }
+}
+
+#[tokio::test]
+async fn equals_detects_different_values() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("FOO", "foo"), ("BAR", "bar")]);
+        let b = env_map(&[("FOO", "foo"), ("BAR", "baz")]);
+        assert!(!*a.equals(b).await?);
+    }
+}
+
+#[tokio::test]
+async fn equals_detects_different_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("FOO", "foo"), ("BAR", "bar")]);
+        let b = env_map(&[("FOO", "foo"), ("BAZ", "bar")]);
+        assert!(!*a.equals(b).await?);
+        let c = env_map(&[("FOO", "foo")]);
+        assert!(!*a.equals(c).await?);
+    }
+}
+
+#[tokio::test]
+async fn map_values_applies_transform() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("FOO", "  foo "), ("BAR", "bar\t"), ("BAZ", "baz")]);
+        let trimmed = map.map_values(TrimVc::new().into());
+        let expected = env_map(&[("FOO", "foo"), ("BAR", "bar"), ("BAZ", "baz")]);
+        assert!(*trimmed.equals(expected).await?);
+    }
+}
+
+#[tokio::test]
+async fn without_keys_preserves_order() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("A", "a"), ("B", "b"), ("C", "c"), ("D", "d")]);
+        let map = map.without_keys(vec!["B".to_string(), "MISSING".to_string()]).await?;
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["A", "C", "D"]);
+    }
+}
+
+#[tokio::test]
+async fn to_json_object_preserves_order() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("ZED", "z"), ("ALPHA", "a"), ("MID", "")]);
+        let json = map.to_json_object().await?;
+        assert_eq!(
+            *json,
+            serde_json::json!({ "ZED": "z", "ALPHA": "a", "MID": "" })
+        );
+        assert_eq!(json.to_string(), r#"{"ZED":"z","ALPHA":"a","MID":""}"#);
+    }
+}
+
+#[tokio::test]
+async fn to_sorted_json_string_sorts_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("ZED", "z"), ("ALPHA", "a"), ("MID", ""), ("Lower", "l")]);
+        let b = env_map(&[("MID", ""), ("Lower", "l"), ("ZED", "z"), ("ALPHA", "a")]);
+        let json = a.to_sorted_json_string().await?;
+        assert_eq!(
+            *json,
+            "{\n  \"ALPHA\": \"a\",\n  \"Lower\": \"l\",\n  \"MID\": \"\",\n  \"ZED\": \"z\"\n}"
+        );
+        assert_eq!(*b.to_sorted_json_string().await?, *json);
+    }
+}
+
+#[tokio::test]
+async fn count_matching_counts_each_key_once() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[
+            ("NEXT_PUBLIC_URL", "/"),
+            ("NEXT_PUBLIC_API_KEY", "key"),
+            ("API_KEY", "key"),
+            ("HOST", "localhost"),
+        ]);
+        let patterns = vec!["NEXT_PUBLIC_*".to_string(), "*_KEY".to_string(), "*".to_string()];
+        assert_eq!(*map.count_matching(patterns).await?, 4);
+        let patterns = vec!["NEXT_PUBLIC_*".to_string(), "*_KEY".to_string()];
+        assert_eq!(*map.count_matching(patterns).await?, 3);
+        assert_eq!(*map.count_matching(vec!["next_public_*".to_string()]).await?, 0);
+        assert_eq!(*map.count_matching(vec![]).await?, 0);
+    }
+}
+
+#[tokio::test]
+async fn intersect_keeps_all_keys_of_overlapping_maps() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("FOO", "foo"), ("BAR", "bar")]);
+        let b = env_map(&[("BAR", "other"), ("FOO", "other")]);
+        let map = a.intersect(b).await?;
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [(&"FOO".to_string(), &"foo".to_string()), (&"BAR".to_string(), &"bar".to_string())]
+        );
+    }
+}
+
+#[tokio::test]
+async fn intersect_keeps_shared_keys_in_order() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("A", "a"), ("B", "b"), ("C", "c"), ("D", "d")]);
+        let b = env_map(&[("D", "other"), ("B", "b"), ("E", "e")]);
+        let map = a.intersect(b).await?;
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["B", "D"]);
+        assert_eq!(map.get("D").map(String::as_str), Some("d"));
+    }
+}
+
+#[tokio::test]
+async fn intersect_of_disjoint_maps_is_empty() {
+    turbo_tasks_env::register();
+    run! {
+        let a = env_map(&[("A", "a"), ("B", "b")]);
+        let b = env_map(&[("C", "c")]);
+        assert!(a.intersect(b).await?.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn apply_defaults_only_fills_missing_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("PORT", "8080"), ("HOST", "example.com")]);
+        let defaults = env_map(&[("LOG_LEVEL", "info"), ("PORT", "3000")]);
+        let map = map.apply_defaults(defaults).await?;
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [
+                (&"PORT".to_string(), &"8080".to_string()),
+                (&"HOST".to_string(), &"example.com".to_string()),
+                (&"LOG_LEVEL".to_string(), &"info".to_string())
+            ]
+        );
+    }
+}
+
+#[tokio::test]
+async fn get_many_keeps_order_of_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("A", "a"), ("B", "b"), ("C", "c")]);
+        let keys = ["C", "MISSING", "A", "b"].map(str::to_string).to_vec();
+        let values = map.get_many(keys).await?;
+        assert_eq!(
+            *values,
+            [Some("c".to_string()), None, Some("a".to_string()), None]
+        );
+    }
+}
+
+#[tokio::test]
+async fn require_all_names_all_missing_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("HOST", "localhost")]);
+        let keys = ["HOST", "PORT", "USER"].map(str::to_string).to_vec();
+        let err = match map.require_all(keys).await {
+            Ok(_) => panic!("expected PORT and USER to be missing"),
+            Err(err) => err,
+        };
+        assert!(format!("{err:#}").contains("missing required env variables: PORT, USER"));
+
+        map.require_all(vec!["HOST".to_string()]).await?;
+    }
+}
+
+#[tokio::test]
+async fn prefixed_prepends_prefix_to_every_key() {
+    turbo_tasks_env::register();
+    run! {
+        let map = env_map(&[("HOST", "localhost"), ("PORT", "5432")]);
+        let prefixed = map.prefixed("DB_").await?;
+        assert_eq!(
+            prefixed.iter().collect::<Vec<_>>(),
+            vec![
+                (&"DB_HOST".to_string(), &"localhost".to_string()),
+                (&"DB_PORT".to_string(), &"5432".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,46 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::fs;
+
+use common::{disk_root, register_deps};
+use turbo_tasks_env::{DotenvProcessEnvVc, JsonFileProcessEnvVc, ProcessEnv, TomlFileProcessEnvVc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn json_file_falls_through_to_prior() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "FOO=prior\nBAR=prior\n")?;
+        fs::write(dir.path().join("env.json"), r#"{ "FOO": "file", "PORT": 3000 }"#)?;
+        let root = disk_root(dir.path());
+        let prior = DotenvProcessEnvVc::new(None, root.join(".env"));
+        let env = JsonFileProcessEnvVc::new(Some(prior.into()), root.join("env.json"));
+
+        assert_eq!(env.read("FOO").await?.as_deref(), Some("file"));
+        assert_eq!(env.read("BAR").await?.as_deref(), Some("prior"));
+        assert_eq!(env.read("PORT").await?.as_deref(), Some("3000"));
+        assert_eq!(env.read_all().await?.len(), 3);
+    }
+}
+
+#[tokio::test]
+async fn toml_file_falls_through_to_prior() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "FOO=prior\nBAR=prior\n")?;
+        fs::write(dir.path().join("env.toml"), "FOO = \"file\"\nDEBUG = true\n")?;
+        let root = disk_root(dir.path());
+        let prior = DotenvProcessEnvVc::new(None, root.join(".env"));
+        let env = TomlFileProcessEnvVc::new(Some(prior.into()), root.join("env.toml"));
+
+        assert_eq!(env.read("FOO").await?.as_deref(), Some("file"));
+        assert_eq!(env.read("BAR").await?.as_deref(), Some("prior"));
+        assert_eq!(env.read("DEBUG").await?.as_deref(), Some("true"));
+    }
+}
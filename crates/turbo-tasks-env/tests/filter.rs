@@ -0,0 +1,75 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::fs;
+
+use common::{disk_root, register_deps};
+use turbo_tasks_env::{DotenvProcessEnvVc, FilterProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn allow_keys_hides_unlisted_keys() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "PUBLIC_URL=/\nSECRET=hunter2\n")?;
+        let inner = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+        let env = FilterProcessEnvVc::allow_keys(inner.into(), vec!["PUBLIC_URL".to_string()]);
+
+        assert_eq!(env.read("PUBLIC_URL").await?.as_deref(), Some("/"));
+        assert_eq!(env.read("SECRET").await?.as_deref(), None);
+        assert!(env.read_with_source("SECRET").await?.is_none());
+        let all = env.read_all().await?;
+        assert_eq!(all.keys().collect::<Vec<_>>(), ["PUBLIC_URL"]);
+    }
+}
+
+#[tokio::test]
+async fn deny_rules_win_over_allow_rules() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".env"),
+            "NEXT_PUBLIC_URL=/\nNEXT_PUBLIC_SECRET=hunter2\nDATABASE_URL=postgres://\n",
+        )?;
+        let inner = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+        let env = FilterProcessEnvVc::with_rules(
+            inner.into(),
+            vec!["NEXT_PUBLIC_*".to_string()],
+            vec!["*_SECRET".to_string()],
+        );
+
+        assert_eq!(env.read("NEXT_PUBLIC_URL").await?.as_deref(), Some("/"));
+        assert_eq!(env.read("NEXT_PUBLIC_SECRET").await?.as_deref(), None);
+        assert_eq!(env.read("DATABASE_URL").await?.as_deref(), None);
+        let all = env.read_all().await?;
+        assert_eq!(all.keys().collect::<Vec<_>>(), ["NEXT_PUBLIC_URL"]);
+
+        let env = FilterProcessEnvVc::with_rules(inner.into(), vec![], vec!["*_SECRET".to_string()]);
+        let all = env.read_all().await?;
+        assert_eq!(all.keys().collect::<Vec<_>>(), ["NEXT_PUBLIC_URL", "DATABASE_URL"]);
+    }
+}
+
+#[tokio::test]
+async fn case_insensitive_matching_can_be_disabled() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "PATH_EXTRA=/opt/bin\n")?;
+        let inner = DotenvProcessEnvVc::new(None, disk_root(dir.path()).join(".env"));
+        let env = FilterProcessEnvVc::with_rules(inner.into(), vec!["path*".to_string()], vec![]);
+
+        let all = env.with_case_insensitive(true).read_all().await?;
+        assert_eq!(all.keys().collect::<Vec<_>>(), ["PATH_EXTRA"]);
+        assert_eq!(env.read("PATH_EXTRA").await?.as_deref(), Some("/opt/bin"));
+
+        let env = env.with_case_insensitive(false);
+        assert!(env.read_all().await?.is_empty());
+        assert_eq!(env.read("PATH_EXTRA").await?.as_deref(), None);
+    }
+}
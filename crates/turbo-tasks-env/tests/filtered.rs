@@ -0,0 +1,103 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::{env, sync::Mutex};
+
+use common::{env_map, vars};
+use indexmap::IndexMap;
+use turbo_tasks_env::{
+    CommandLineProcessEnvVc, CustomProcessEnvVc, EnvMapVc, ProcessEnv, ProcessEnvVc,
+};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+/// The names of the variables [RecordingEnv] has cloned into a map.
+static MATERIALIZED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// An env that records every variable it clones into a map, so a test can
+/// check which values ended up in the cache.
+#[turbo_tasks::value]
+struct RecordingEnv {
+    #[turbo_tasks(trace_ignore)]
+    vars: IndexMap<String, String>,
+}
+
+impl RecordingEnv {
+    fn materialize<'a>(&self, vars: impl Iterator<Item = (&'a String, &'a String)>) -> EnvMapVc {
+        let vars = vars
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<IndexMap<_, _>>();
+        MATERIALIZED.lock().unwrap().extend(vars.keys().cloned());
+        EnvMapVc::cell(vars)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for RecordingEnv {
+    #[turbo_tasks::function]
+    fn read_all(&self) -> EnvMapVc {
+        self.materialize(self.vars.iter())
+    }
+
+    #[turbo_tasks::function]
+    fn read_all_filtered(&self, prefix: &str) -> EnvMapVc {
+        let prefix = prefix.to_uppercase();
+        self.materialize(
+            self.vars
+                .iter()
+                .filter(|(key, _)| key.to_uppercase().starts_with(&prefix)),
+        )
+    }
+}
+
+const VARS: &[(&str, &str)] = &[
+    ("NEXT_PUBLIC_URL", "/"),
+    ("SECRET", "hunter2"),
+    ("next_public_id", "1"),
+];
+
+#[tokio::test]
+async fn read_all_filtered_keeps_matching_keys() {
+    turbo_tasks_env::register();
+    run! {
+        let env = CustomProcessEnvVc::new(env_map(VARS));
+        let filtered = env.read_all_filtered("NEXT_PUBLIC_").await?;
+        assert_eq!(filtered.keys().collect::<Vec<_>>(), ["NEXT_PUBLIC_URL", "next_public_id"]);
+    }
+}
+
+#[tokio::test]
+async fn overridden_read_all_filtered_materializes_only_matching_keys() {
+    turbo_tasks_env::register();
+    run! {
+        MATERIALIZED.lock().unwrap().clear();
+        let env: ProcessEnvVc = RecordingEnv { vars: vars(VARS) }.cell().into();
+        let filtered = env.read_all_filtered("NEXT_PUBLIC_").await?;
+        assert_eq!(filtered.keys().collect::<Vec<_>>(), ["NEXT_PUBLIC_URL", "next_public_id"]);
+        assert_eq!(*MATERIALIZED.lock().unwrap(), ["NEXT_PUBLIC_URL", "next_public_id"]);
+
+        // Reading everything does clone the other values.
+        env.read_all().await?;
+        assert!(MATERIALIZED.lock().unwrap().iter().any(|key| key == "SECRET"));
+    }
+}
+
+#[tokio::test]
+async fn command_line_read_all_filtered_only_reads_matching_keys() {
+    turbo_tasks_env::register();
+    env::set_var("FILTERED_TEST_PUBLIC_URL", "/");
+    env::set_var("FILTERED_TEST_PUBLIC_TOKEN", "hunter2");
+    env::set_var("UNFILTERED_TEST_VALUE", "1");
+    run! {
+        let env = CommandLineProcessEnvVc::with_options(true);
+        let filtered = env.read_all_filtered("filtered_test_").await?;
+        assert_eq!(filtered.keys().collect::<Vec<_>>(), ["FILTERED_TEST_PUBLIC_URL"]);
+
+        let env = CommandLineProcessEnvVc::new();
+        let filtered = env.read_all_filtered("FILTERED_TEST_").await?;
+        assert_eq!(filtered.len(), 2);
+        assert!(!filtered.contains_key("UNFILTERED_TEST_VALUE"));
+    }
+}
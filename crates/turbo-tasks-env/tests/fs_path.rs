@@ -0,0 +1,39 @@
+#![feature(min_specialization)]
+
+mod common;
+
+use std::fs;
+
+use common::{disk_root, register_deps};
+use turbo_tasks_env::{DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_fs_path_resolves_relative_and_absolute_paths() {
+    register_deps();
+    run! {
+        let dir = tempfile::tempdir()?;
+        let output_path = dir.path().join("out").join("dist");
+        fs::write(
+            dir.path().join(".env"),
+            format!(
+                "CACHE_DIR=.cache\nOUTPUT_PATH={}\nOUTSIDE=/\n",
+                output_path.display()
+            ),
+        )?;
+        let root = disk_root(dir.path());
+        let env = DotenvProcessEnvVc::new(None, root.join(".env"));
+        let app = root.join("app");
+
+        let cache_dir = env.read_fs_path("CACHE_DIR", app).await?.unwrap();
+        assert_eq!(cache_dir.await?.path, "app/.cache");
+
+        let output_path = env.read_fs_path("OUTPUT_PATH", app).await?.unwrap();
+        assert_eq!(output_path.await?.path, "out/dist");
+
+        assert!(env.read_fs_path("OUTSIDE", app).await?.is_none());
+        assert!(env.read_fs_path("MISSING", app).await?.is_none());
+    }
+}
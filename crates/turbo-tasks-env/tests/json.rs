@@ -0,0 +1,26 @@
+#![feature(min_specialization)]
+
+use serde_json::json;
+use turbo_tasks_env::{DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_json_parses_values() {
+    turbo_tasks_env::register();
+    run! {
+        let env = DotenvProcessEnvVc::from_string(
+            "FEATURE_FLAGS='{\"beta\":true,\"rollout\":[1,2]}'\nBROKEN='{\"beta\":'\n".to_string(),
+            None,
+        );
+
+        assert_eq!(
+            *env.read_json("FEATURE_FLAGS").await?,
+            json!({ "beta": true, "rollout": [1, 2] })
+        );
+        assert_eq!(*env.read_json("MISSING").await?, serde_json::Value::Null);
+        let error = env.read_json("BROKEN").await.unwrap_err();
+        assert!(format!("{error:#}").contains("env variable BROKEN is not valid JSON"));
+    }
+}
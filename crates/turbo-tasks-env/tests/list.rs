@@ -0,0 +1,25 @@
+#![feature(min_specialization)]
+
+use turbo_tasks_env::{DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_list_splits_trims_and_drops_empty_entries() {
+    turbo_tasks_env::register();
+    run! {
+        let env = DotenvProcessEnvVc::from_string(
+            "ALLOWED_ORIGINS=\" a.com, b.com ,c.com,\"\nSEARCH_PATH=/usr/bin::/bin:\n"
+                .to_string(),
+            None,
+        );
+
+        assert_eq!(
+            *env.read_list("ALLOWED_ORIGINS", ",").await?,
+            ["a.com", "b.com", "c.com"]
+        );
+        assert_eq!(*env.read_list("SEARCH_PATH", ":").await?, ["/usr/bin", "/bin"]);
+        assert!(env.read_list("MISSING", ",").await?.is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+#![feature(min_specialization)]
+
+use turbo_tasks_env::{DotenvProcessEnvVc, ProcessEnv};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn pretty_sorts_aligns_and_masks() {
+    turbo_tasks_env::register();
+    run! {
+        let env = DotenvProcessEnvVc::from_string(
+            "PORT=3000\nAPI_TOKEN=hunter2\nHOST=localhost\n".to_string(),
+            None,
+        );
+
+        assert_eq!(
+            &*env.pretty().await?,
+            "API_TOKEN = ********\nHOST      = localhost\nPORT      = 3000\n"
+        );
+    }
+}
@@ -2,7 +2,9 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
-use turbopack_create_test_app::test_app_builder::TestAppBuilder;
+use turbopack_create_test_app::test_app_builder::{
+    FragmentStyle, Framework, PackageJsonConfig, TestAppBuilder,
+};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -11,6 +13,10 @@ struct Args {
     #[clap(value_name = "DIR", value_parser, default_value = ".")]
     target: PathBuf,
 
+    /// Keep the files created by a failed build
+    #[clap(long)]
+    keep_partial_output: bool,
+
     /// The number of modules to generate
     #[clap(short, long, value_parser, default_value_t = 1000)]
     modules: usize,
@@ -27,9 +33,234 @@ struct Args {
     #[clap(short, long, value_parser, default_value_t = 4)]
     flatness: usize,
 
+    /// The flatness to use at each depth, the last value applies to deeper
+    /// levels
+    #[clap(long, value_parser, value_delimiter = ',')]
+    flatness_profile: Vec<usize>,
+
+    /// The fraction of modules using a named export instead of a default export
+    #[clap(long, value_parser, default_value_t = 0.0)]
+    named_export_ratio: f32,
+
+    /// The fraction of modules wrapped in React.forwardRef
+    #[clap(long, value_parser, default_value_t = 0.0)]
+    forward_ref_ratio: f32,
+
+    /// The maximum depth of the component tree
+    #[clap(long, value_parser)]
+    max_depth: Option<usize>,
+
+    /// The locales to generate message catalogs for
+    #[clap(long, value_parser, value_delimiter = ',')]
+    locales: Vec<String>,
+
+    /// Generate a .gitignore and a README.md describing the app
+    #[clap(long)]
+    scaffold_repo: bool,
+
+    /// The number of additional pages to generate in the pages directory
+    #[clap(long, value_parser, default_value_t = 0)]
+    pages: usize,
+
+    /// The number of modules reading a VITE_* variable via import.meta.env
+    #[clap(long, value_parser, default_value_t = 0)]
+    import_meta_env: usize,
+
+    /// The number of NEXT_PUBLIC_* variables required by a next.config.js
+    #[clap(long, value_parser, default_value_t = 0)]
+    next_public_env: usize,
+
+    /// The number of modules importing a .graphql document
+    #[clap(long, value_parser, default_value_t = 0)]
+    graphql_imports: usize,
+
+    /// The number of modules rendering into a React portal
+    #[clap(long, value_parser, default_value_t = 0)]
+    portals: usize,
+
+    /// The number of modules shared by two leaf modules, forming diamonds in
+    /// the module graph
+    #[clap(long, value_parser, default_value_t = 0)]
+    diamonds: usize,
+
+    /// The number of modules returning a deeply nested conditional expression
+    #[clap(long, value_parser, default_value_t = 0)]
+    deep_expressions: usize,
+
+    /// The nesting depth of the deeply nested conditional expressions
+    #[clap(long, value_parser, default_value_t = 50)]
+    deep_expression_depth: usize,
+
+    /// The number of modules importing a .json file
+    #[clap(long, value_parser, default_value_t = 0)]
+    json_imports: usize,
+
+    /// Import .json files with import attributes
+    #[clap(long)]
+    json_import_attributes: bool,
+
+    /// Generate a Next.js middleware.js
+    #[clap(long)]
+    middleware: bool,
+
+    /// The number of "use server" actions submitted by forms in the app router
+    #[clap(long, value_parser, default_value_t = 0)]
+    server_actions: usize,
+
+    /// The number of nested app router segments with their own layout
+    #[clap(long, value_parser, default_value_t = 0)]
+    nested_layouts: usize,
+
+    /// The names of app router route groups to generate
+    #[clap(long, value_parser, value_delimiter = ',')]
+    route_groups: Vec<String>,
+
+    /// Generate the app router sitemap, robots and opengraph image metadata
+    /// routes
+    #[clap(long)]
+    metadata_routes: bool,
+
+    /// The number of node: builtin imports of each server module
+    #[clap(long, value_parser, default_value_t = 0)]
+    node_builtin_imports: usize,
+
+    /// The number of dynamic imports with computed specifiers
+    #[clap(long, value_parser, default_value_t = 0)]
+    dynamic_expressions: usize,
+
+    /// The number of SharedWorkers to generate
+    #[clap(long, value_parser, default_value_t = 0)]
+    shared_workers: usize,
+
+    /// The number of audio worklet processors to generate
+    #[clap(long, value_parser, default_value_t = 0)]
+    audio_worklets: usize,
+
+    /// The number of TypeScript classes using legacy decorators, requires
+    /// --typescript
+    #[clap(long, value_parser, default_value_t = 0)]
+    decorators: usize,
+
+    /// The number of `import type` declarations of type-only modules, requires
+    /// --typescript
+    #[clap(long, value_parser, default_value_t = 0)]
+    type_only_imports: usize,
+
+    /// The length of the chain of CSS files @import-ing each other
+    #[clap(long, value_parser, default_value_t = 0)]
+    css_import_depth: usize,
+
+    /// The number of CSS custom properties defined by the theme
+    #[clap(long, value_parser, default_value_t = 0)]
+    css_theme_vars: usize,
+
+    /// The UI framework to generate the app for
+    #[clap(long, value_enum, default_value_t = Framework::React)]
+    framework: Framework,
+
+    /// Write the Vue components and the metadata routes in TypeScript, required
+    /// by --decorators and --type-only-imports
+    #[clap(long)]
+    typescript: bool,
+
+    /// How the components wrap their children in a fragment
+    #[clap(long, value_enum, default_value_t = FragmentStyle::Shorthand)]
+    fragment_style: FragmentStyle,
+
+    /// Generate a Tailwind setup and use utility classes in components
+    #[clap(long)]
+    tailwind: bool,
+
+    /// Use styled-components wrappers in a subset of the leaf components
+    #[clap(long)]
+    styled_components: bool,
+
+    /// Generate SCSS files using nesting and shared mixins for a subset of the
+    /// leaf components
+    #[clap(long)]
+    scss: bool,
+
+    /// The number of modules importing a sibling .css file
+    #[clap(long, value_parser, default_value_t = 0)]
+    css_files: usize,
+
+    /// The fraction of the sibling .css files imported as a CSS module
+    #[clap(long, value_parser, default_value_t = 0.5)]
+    css_module_ratio: f32,
+
+    /// Generate a workspace package with exports and imports maps, imported
+    /// via subpaths
+    #[clap(long)]
+    subpath_exports: bool,
+
+    /// Give dynamic imports a Suspense fallback
+    #[clap(long)]
+    suspense_fallback: bool,
+
+    /// The number of class-based error boundaries wrapping subtrees
+    #[clap(long, value_parser, default_value_t = 0)]
+    error_boundaries: usize,
+
+    /// Throw from a component inside the first error boundary on its first
+    /// render
+    #[clap(long)]
+    error_boundary_throw: bool,
+
+    /// Generate snapshot tests for a subset of the components
+    #[clap(long)]
+    unit_tests: bool,
+
+    /// Add helper functions, constants and JSDoc to each component module
+    #[clap(long)]
+    realistic_boilerplate: bool,
+
+    /// A license header to prepend as a /*! */ comment to each component
+    /// module
+    #[clap(long, value_parser)]
+    license_header: Option<String>,
+
+    /// Name the modules after their depth, role and index, e.g.
+    /// d5_leaf_123.jsx
+    #[clap(long)]
+    descriptive_names: bool,
+
+    /// Spread the modules over the .js, .jsx, .ts, .tsx, .mjs and .cjs
+    /// extensions
+    #[clap(long)]
+    extension_mix: bool,
+
+    /// Generate a multi-stage Dockerfile and a .dockerignore
+    #[clap(long)]
+    dockerfile: bool,
+
+    /// The number of static files to generate in public/assets
+    #[clap(long, value_parser, default_value_t = 0)]
+    public_files: usize,
+
     /// Generate a package.json with required dependencies
     #[clap(long)]
     package_json: bool,
+
+    /// Additional dependencies of the package.json as name@version, e.g.
+    /// lodash@^4.17.21
+    #[clap(long, value_parser, value_delimiter = ',')]
+    extra_dependencies: Vec<String>,
+
+    /// Import the additional dependencies from the component modules
+    #[clap(long)]
+    resolve_real_deps: bool,
+}
+
+/// Splits `name@version` into its name and version, the version defaults to
+/// `*`. The leading `@` of a scoped package isn't a separator.
+fn parse_dependency(dependency: &str) -> (String, String) {
+    match dependency.rfind('@') {
+        Some(index) if index > 0 => (
+            dependency[..index].to_string(),
+            dependency[index + 1..].to_string(),
+        ),
+        _ => (dependency.to_string(), "*".to_string()),
+    }
 }
 
 fn main() -> Result<()> {
@@ -39,12 +270,69 @@ fn main() -> Result<()> {
         "{}",
         TestAppBuilder {
             target: Some(args.target),
+            cleanup_on_error: !args.keep_partial_output,
             module_count: args.modules,
             directories_count: args.directories,
             dynamic_import_count: args.dynamic_imports,
             flatness: args.flatness,
+            flatness_profile: args.flatness_profile,
+            named_export_ratio: args.named_export_ratio,
+            forward_ref_ratio: args.forward_ref_ratio,
+            max_depth: args.max_depth,
+            locales: args.locales,
+            scaffold_repo: args.scaffold_repo,
+            pages_router_page_count: args.pages,
+            import_meta_env_count: args.import_meta_env,
+            next_public_env_count: args.next_public_env,
+            graphql_import_count: args.graphql_imports,
+            portal_count: args.portals,
+            diamond_count: args.diamonds,
+            deep_expression_count: args.deep_expressions,
+            deep_expression_depth: args.deep_expression_depth,
+            json_import_count: args.json_imports,
+            json_import_attributes: args.json_import_attributes,
+            middleware: args.middleware,
+            server_actions: args.server_actions,
+            nested_layouts: args.nested_layouts,
+            route_groups: args.route_groups,
+            metadata_routes: args.metadata_routes,
+            node_builtin_imports: args.node_builtin_imports,
+            dynamic_expression_count: args.dynamic_expressions,
+            shared_worker_count: args.shared_workers,
+            audio_worklet_count: args.audio_worklets,
+            decorators: args.decorators,
+            type_only_import_count: args.type_only_imports,
+            css_import_depth: args.css_import_depth,
+            css_theme_vars: args.css_theme_vars,
+            framework: args.framework,
+            typescript: args.typescript,
+            fragment_style: args.fragment_style,
+            tailwind: args.tailwind,
+            styled_components: args.styled_components,
+            scss: args.scss,
+            css_file_count: args.css_files,
+            css_module_ratio: args.css_module_ratio,
+            subpath_exports: args.subpath_exports,
+            resolve_real_deps: args.resolve_real_deps,
+            suspense_fallback: args.suspense_fallback,
+            error_boundaries: args.error_boundaries,
+            error_boundary_throw: args.error_boundary_throw,
+            unit_tests: args.unit_tests,
+            realistic_boilerplate: args.realistic_boilerplate,
+            license_header: args.license_header,
+            descriptive_names: args.descriptive_names,
+            extension_mix: args.extension_mix,
+            dockerfile: args.dockerfile,
+            public_file_count: args.public_files,
             package_json: if args.package_json {
-                Some(Default::default())
+                Some(PackageJsonConfig {
+                    extra_dependencies: args
+                        .extra_dependencies
+                        .iter()
+                        .map(|dependency| parse_dependency(dependency))
+                        .collect(),
+                    ..Default::default()
+                })
             } else {
                 None
             }
@@ -0,0 +1,1398 @@
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, File},
+    io::prelude::*,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use super::{
+    custom_element_name, into_named_export, relative_import, test_app::TestApp, Framework,
+    TestAppBuilder, LOCALE_MESSAGE_COUNT, SHARED_PACKAGE_NAME, THEME_VAR_USE_INTERVAL,
+};
+
+/// Every n-th leaf module looks up a message from a locale catalog.
+const LOCALE_LOOKUP_INTERVAL: usize = 10;
+
+/// Every n-th leaf module uses Tailwind utility classes when `tailwind` is
+/// enabled.
+const TAILWIND_CLASS_INTERVAL: usize = 5;
+
+/// Every n-th leaf module renders `styled-components` wrappers when
+/// `styled_components` is enabled.
+const STYLED_COMPONENTS_INTERVAL: usize = 4;
+
+/// Every n-th leaf module imports its own `.scss` file when `scss` is enabled.
+const SCSS_INTERVAL: usize = 6;
+
+/// Every n-th leaf module imports a subpath of the shared package when
+/// `subpath_exports` is enabled.
+const SUBPATH_IMPORT_INTERVAL: usize = 3;
+
+/// Every n-th leaf module imports one of the `extra_dependencies` of the
+/// [PackageJsonConfig](super::PackageJsonConfig) when `resolve_real_deps` is
+/// enabled.
+const REAL_DEPENDENCY_IMPORT_INTERVAL: usize = 4;
+
+/// Every n-th leaf module uses one of the classes of
+/// `src/styles/components.css` when `css_theme_vars` is set.
+const THEME_CLASS_INTERVAL: usize = 2;
+
+/// Every n-th leaf module gets a unit test when `unit_tests` is enabled.
+const UNIT_TEST_INTERVAL: usize = 10;
+
+/// Returns the `defineProps` call of a [Framework::Vue] component, typed when
+/// the component is written in TypeScript.
+fn vue_define_props(typescript: bool) -> &'static str {
+    if typescript {
+        "defineProps<{ style: Record<string, string> }>();"
+    } else {
+        "defineProps([\"style\"]);"
+    }
+}
+
+/// Returns the leaf component of [Framework::Vue] without the opening
+/// `<script setup>` tag, which is added by `finish_vue_component`.
+fn vue_leaf(typescript: bool) -> String {
+    format!(
+        r#"{}
+</script>
+
+<template>
+    <polygon points="-5,4.33 0,-4.33 5,4.33" :style="style" />
+</template>
+"#,
+        vue_define_props(typescript)
+    )
+}
+
+/// Returns the leaf module of [Framework::WebComponents], defining the custom
+/// element `name`.
+fn web_component_leaf(name: &str) -> String {
+    format!(
+        r#"class Triangle extends HTMLElement {{
+    connectedCallback() {{
+        if (this.shadowRoot) {{
+            return;
+        }}
+        const fill = this.getAttribute("fill") || "white";
+        this.attachShadow({{ mode: "open" }}).innerHTML = `<svg viewBox="-5 -4.33 10 8.66" style="display: block; width: 100%; height: 100%;">
+    <polygon points="-5,4.33 0,-4.33 5,4.33" fill="${{fill}}" />
+</svg>`;
+    }}
+}}
+
+customElements.define("{name}", Triangle);
+
+export default Triangle;
+"#
+    )
+}
+
+/// The leaf module of the React templates without any leaf feature.
+const LEAF: &str = r#"import React from "react";
+
+function Triangle({ style }) {
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={style} />;
+}
+
+export default React.memo(Triangle);
+"#;
+
+/// The leaf module for the `.ts` and `.mjs` extensions of `extension_mix`,
+/// which don't allow JSX.
+const CREATE_ELEMENT_LEAF: &str = r#"import React from "react";
+
+function Triangle({ style }) {
+    return React.createElement("polygon", { points: "-5,4.33 0,-4.33 5,4.33", style });
+}
+
+export default React.memo(Triangle);
+"#;
+
+/// The leaf module of `forward_ref_ratio` forwarding the ref to its polygon.
+const FORWARD_REF_LEAF: &str = r#"import React from "react";
+
+const Triangle = React.forwardRef(function Triangle({ style }, ref) {
+    return <polygon ref={ref} points="-5,4.33 0,-4.33 5,4.33" style={style} />;
+});
+
+export default React.memo(Triangle);
+"#;
+
+/// The leaf module of `forward_ref_ratio` exposing a handle instead of its
+/// polygon.
+const IMPERATIVE_HANDLE_LEAF: &str = r#"import React from "react";
+
+const Triangle = React.forwardRef(function Triangle({ style }, ref) {
+    const polygon = React.useRef(null);
+    React.useImperativeHandle(ref, () => ({
+        highlight() {
+            polygon.current.style.opacity = "0.5";
+        },
+    }));
+    return <polygon ref={polygon} points="-5,4.33 0,-4.33 5,4.33" style={style} />;
+});
+
+export default React.memo(Triangle);
+"#;
+
+/// The leaf module of `tailwind` using utility classes.
+const TAILWIND_LEAF: &str = r#"import React from "react";
+
+function Triangle({ style }) {
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={style} className="opacity-75 hover:opacity-100" />;
+}
+
+export default React.memo(Triangle);
+"#;
+
+/// The leaf module of `styled_components`.
+const STYLED_COMPONENTS_LEAF: &str = r#"import React from "react";
+import styled from "styled-components";
+
+const Group = styled.g`
+    opacity: 0.75;
+`;
+
+const Polygon = styled.polygon`
+    transition: opacity 0.2s;
+
+    &:hover {
+        opacity: 0.5;
+    }
+`;
+
+function Triangle({ style }) {
+    return <Group>
+        <Polygon points="-5,4.33 0,-4.33 5,4.33" style={style} />
+    </Group>;
+}
+
+export default React.memo(Triangle);
+"#;
+
+/// Returns the leaf module for the `.cjs` extension of `extension_mix`,
+/// assigning the component to `export`.
+fn cjs_leaf(export: &str) -> String {
+    format!(
+        r#"const React = require("react");
+
+function Triangle({{ style }}) {{
+    return React.createElement("polygon", {{ points: "-5,4.33 0,-4.33 5,4.33", style }});
+}}
+
+{export} = React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `locales` rendering `message` of the catalog
+/// imported from `locale_import`.
+fn locale_leaf(locale_import: &str, message: &str) -> String {
+    format!(
+        r#"import React from "react";
+import messages from "{locale_import}";
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}}>
+        <title>{{messages.{message}}}</title>
+    </polygon>;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `import_meta_env_count` reading `env_var`.
+fn import_meta_env_leaf(env_var: &str) -> String {
+    format!(
+        r#"import React from "react";
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}}>
+        <title>{{import.meta.env.{env_var}}}</title>
+    </polygon>;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `deep_expression_count` returning a conditional
+/// expression nested `depth` levels deep.
+fn deep_expression_leaf(depth: usize) -> String {
+    let mut expression = String::new();
+    for level in 0..depth {
+        expression.push_str(&format!(
+            "(level === {level}\n        ? <polygon points=\"-5,4.33 0,-4.33 5,4.33\" \
+             style={{style}} data-level={{{level}}} />\n        : "
+        ));
+    }
+    expression.push_str("<polygon points=\"-5,4.33 0,-4.33 5,4.33\" style={style} />");
+    expression.push_str(&")".repeat(depth));
+    format!(
+        r#"import React from "react";
+
+function Triangle({{ style }}) {{
+    const level = Object.keys(style || {{}}).length;
+    return {expression};
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns the `n`th leaf module of `portal_count`.
+fn portal_leaf(n: usize) -> String {
+    format!(
+        r#"import React from "react";
+import {{ createPortal }} from "react-dom";
+
+function portalTarget() {{
+    let target = document.getElementById("portal-{n}");
+    if (!target) {{
+        target = document.createElement("div");
+        target.id = "portal-{n}";
+        document.body.appendChild(target);
+    }}
+    return target;
+}}
+
+function Triangle({{ style }}) {{
+    const polygon = <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />;
+    // There is no document to portal into during server rendering.
+    if (typeof document === "undefined") {{
+        return polygon;
+    }}
+    return <>
+        {{polygon}}
+        {{createPortal(
+            <svg height="10%" viewBox="-5 -4.33 10 8.66">{{polygon}}</svg>,
+            portalTarget()
+        )}}
+    </>;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `resolve_real_deps` importing `specifier`.
+fn real_dependency_leaf(specifier: &str) -> String {
+    format!(
+        r#"import React from "react";
+import * as dependency from "{specifier}";
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} data-dependency={{typeof dependency}} />;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `css_theme_vars` using the class of the `theme`th
+/// custom property.
+fn theme_leaf(theme: usize) -> String {
+    format!(
+        r#"import React from "react";
+
+function Triangle({{ style }}) {{
+    return <g className="theme-{theme}">
+        <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />
+    </g>;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Returns a leaf module of `subpath_exports` importing a subpath of the
+/// shared package.
+fn subpath_leaf() -> String {
+    format!(
+        r#"import React from "react";
+import {{ fill }} from "{SHARED_PACKAGE_NAME}/colors";
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{{{ ...style, fill }}}} />;
+}}
+
+export default React.memo(Triangle);
+"#
+    )
+}
+
+/// Writes the module with children `file`.
+pub(super) fn write_container(file: &Path, content: &str) -> Result<()> {
+    File::create(file)
+        .with_context(|| format!("creating file with children {}", file.display()))?
+        .write_all(content.as_bytes())
+        .with_context(|| format!("writing file with children {}", file.display()))
+}
+
+/// Returns the module with children of [Framework::Astro]. The lazy children
+/// are rendered as islands.
+pub(super) fn astro_container(
+    file: &Path,
+    children: &[PathBuf],
+    lazy_files: &HashSet<PathBuf>,
+) -> String {
+    let mut imports = String::new();
+    let mut elements = Vec::with_capacity(3);
+    for (child, name) in children.iter().zip(["A", "B", "C"]) {
+        imports.push_str(&format!(
+            "import {name} from \"{}\";\n",
+            relative_import(file, child)
+        ));
+        elements.push(if lazy_files.contains(child) {
+            format!("<{name} client:load style={{style}} />")
+        } else {
+            format!("<{name} style={{style}} />")
+        });
+    }
+    let [a_, b_, c_] = [&elements[0], &elements[1], &elements[2]];
+    format!(
+        r#"---
+{imports}
+const {{ style }} = Astro.props;
+---
+<g transform="translate(0 -2.16)   scale(0.5 0.5)">
+    {a_}
+</g>
+<g transform="translate(-2.5 2.16) scale(0.5 0.5)">
+    {b_}
+</g>
+<g transform="translate(2.5 2.16)  scale(0.5 0.5)">
+    {c_}
+</g>
+"#
+    )
+}
+
+/// Returns the module with children of [Framework::WebComponents]. The lazy
+/// children are imported when the element is connected.
+pub(super) fn web_component_container(
+    src: &Path,
+    file: &Path,
+    children: &[PathBuf],
+    lazy_files: &HashSet<PathBuf>,
+) -> String {
+    let mut imports = String::new();
+    let mut lazy_imports = String::new();
+    let mut elements = String::new();
+    for (child, class) in children.iter().zip(["a", "b", "c"]) {
+        let specifier = relative_import(file, child);
+        if lazy_files.contains(child) {
+            lazy_imports.push_str(&format!("        import(\"{specifier}\");\n"));
+        } else {
+            imports.push_str(&format!("import \"{specifier}\";\n"));
+        }
+        let name = custom_element_name(src, child);
+        elements.push_str(&format!(
+            "\n<{name} class=\"child {class}\" fill=\"${{fill}}\"></{name}>"
+        ));
+    }
+    let name = custom_element_name(src, file);
+    format!(
+        r#"{imports}
+class Triangle extends HTMLElement {{
+    connectedCallback() {{
+        if (this.shadowRoot) {{
+            return;
+        }}
+{lazy_imports}        const fill = this.getAttribute("fill") || "white";
+        this.attachShadow({{ mode: "open" }}).innerHTML = `<style>
+    :host {{ display: block; position: relative; width: 100%; height: 100%; }}
+    .child {{ position: absolute; width: 50%; height: 50%; }}
+    .a {{ left: 25%; top: 0; }}
+    .b {{ left: 0; top: 50%; }}
+    .c {{ left: 50%; top: 50%; }}
+</style>{elements}`;
+    }}
+}}
+
+customElements.define("{name}", Triangle);
+
+export default Triangle;
+"#
+    )
+}
+
+/// Returns the module with children of [Framework::Vue] without the opening
+/// `<script setup>` tag. The lazy children are async components.
+pub(super) fn vue_container(
+    file: &Path,
+    children: &[PathBuf],
+    lazy_files: &HashSet<PathBuf>,
+    typescript: bool,
+) -> String {
+    let mut imports = String::new();
+    let mut lazy_imports = String::new();
+    for (child, name) in children.iter().zip(["A", "B", "C"]) {
+        let specifier = relative_import(file, child);
+        if lazy_files.contains(child) {
+            lazy_imports.push_str(&format!(
+                "const {name} = defineAsyncComponent(() => import(\"{specifier}\"));\n"
+            ));
+        } else {
+            imports.push_str(&format!("import {name} from \"{specifier}\";\n"));
+        }
+    }
+    if !lazy_imports.is_empty() {
+        imports.insert_str(0, "import { defineAsyncComponent } from \"vue\";\n");
+    }
+    format!(
+        r#"{imports}{lazy_imports}
+{}
+</script>
+
+<template>
+    <g transform="translate(0 -2.16)   scale(0.5 0.5)">
+        <A :style="style" />
+    </g>
+    <g transform="translate(-2.5 2.16) scale(0.5 0.5)">
+        <B :style="style" />
+    </g>
+    <g transform="translate(2.5 2.16)  scale(0.5 0.5)">
+        <C :style="style" />
+    </g>
+</template>
+"#,
+        vue_define_props(typescript)
+    )
+}
+
+/// Returns the module with children of [Framework::Qwik], importing its
+/// children from `{import_path}N`.
+pub(super) fn qwik_container(import_path: &str) -> String {
+    format!(
+        r#"import {{ component$ }} from "@builder.io/qwik";
+import A from "{import_path}1";
+import B from "{import_path}2";
+import C from "{import_path}3";
+
+export default component$((props: {{ style: Record<string, string> }}) => {{
+    return <>
+        <g transform="translate(0 -2.16)   scale(0.5 0.5)">
+            <A style={{props.style}} />
+        </g>
+        <g transform="translate(-2.5 2.16) scale(0.5 0.5)">
+            <B style={{props.style}} />
+        </g>
+        <g transform="translate(2.5 2.16)  scale(0.5 0.5)">
+            <C style={{props.style}} />
+        </g>
+    </>;
+}});
+"#
+    )
+}
+
+/// The state shared by the leaf modules while building an app, e.g. how many
+/// of them already use a leaf feature.
+pub(super) struct Leaves {
+    pub(super) src: PathBuf,
+    /// The number of leaf modules, including the one being written.
+    pub(super) count: usize,
+    /// The variables read via `import.meta.env` by the leaf modules.
+    pub(super) env_vars: Vec<String>,
+    /// The number of leaf modules forwarding a ref.
+    pub(super) forward_refs: usize,
+    /// The number of leaf modules importing a shared module of
+    /// `diamond_count`, the first of each pair creates the shared module.
+    pub(super) diamonds: usize,
+    /// The number of `css_file_count` stylesheets imported as a CSS module.
+    pub(super) css_modules: usize,
+    /// The specifiers of the `extra_dependencies` with `resolve_real_deps`.
+    pub(super) real_dependencies: Vec<String>,
+    pub(super) real_dependency_imports: usize,
+    /// The number of leaf modules using a class of `css_theme_vars`.
+    pub(super) theme_classes: usize,
+}
+
+impl TestAppBuilder {
+    /// Writes the `i`th class-based error boundary of `error_boundaries` and
+    /// returns its path.
+    pub(super) fn write_error_boundary(
+        &self,
+        error_boundaries_dir: &Path,
+        i: usize,
+    ) -> Result<PathBuf> {
+        let boundary_file = error_boundaries_dir.join(format!("error_boundary_{i}.jsx"));
+        let boundary = format!(
+            r#"import React from "react";
+
+export default class ErrorBoundary{i} extends React.Component {{
+    constructor(props) {{
+        super(props);
+        this.state = {{ hasError: false }};
+    }}
+
+    static getDerivedStateFromError() {{
+        return {{ hasError: true }};
+    }}
+
+    componentDidCatch(error, info) {{
+        console.error("ErrorBoundary{i} caught", error, info.componentStack);
+    }}
+
+    render() {{
+        if (this.state.hasError) {{
+            return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{{{ ...this.props.style, opacity: 0.2 }}}} />;
+        }}
+        return this.props.children;
+    }}
+}}
+"#
+        );
+        File::create(&boundary_file)
+            .context("creating error boundary")?
+            .write_all(boundary.as_bytes())
+            .context("writing error boundary")?;
+        Ok(boundary_file)
+    }
+
+    /// Writes the leaf module `file`, and its unit test with `unit_tests`.
+    /// The module uses the first leaf feature that applies to it, in the
+    /// order documented on [TestAppBuilder].
+    pub(super) fn write_leaf(
+        &self,
+        app: &mut TestApp,
+        leaves: &mut Leaves,
+        file: &Path,
+        forward_ref: bool,
+        is_island: bool,
+    ) -> Result<()> {
+        let locale = if !app.locales.is_empty()
+            && (leaves.count < app.locales.len() || leaves.count % LOCALE_LOOKUP_INTERVAL == 0)
+        {
+            Some(app.locales[leaves.count % app.locales.len()].clone())
+        } else {
+            None
+        };
+        leaves.count += 1;
+        let native_leaf = self.framework.native_leaf().filter(|_| !is_island);
+        let extension = file.extension().unwrap().to_str().unwrap();
+        let leaf_content = if let Some(leaf_content) = native_leaf {
+            leaf_content.to_string()
+        } else if self.framework == Framework::WebComponents {
+            web_component_leaf(&custom_element_name(&leaves.src, file))
+        } else if self.framework == Framework::Vue {
+            vue_leaf(self.typescript)
+        } else if forward_ref {
+            leaves.forward_refs += 1;
+            if leaves.forward_refs % 2 == 0 {
+                IMPERATIVE_HANDLE_LEAF.to_string()
+            } else {
+                FORWARD_REF_LEAF.to_string()
+            }
+        } else if extension == "cjs" {
+            cjs_leaf(if app.named_exports.contains(file) {
+                "module.exports.Triangle"
+            } else {
+                "module.exports"
+            })
+        } else if extension == "ts" || extension == "mjs" {
+            CREATE_ELEMENT_LEAF.to_string()
+        } else if let Some(locale) = locale {
+            let message = format!("message_{}", leaves.count % LOCALE_MESSAGE_COUNT);
+            locale_leaf(&relative_import(file, &locale), &message)
+        } else if leaves.env_vars.len() < self.import_meta_env_count {
+            let env_var = format!("VITE_TRIANGLE_{}", leaves.env_vars.len());
+            let leaf_content = import_meta_env_leaf(&env_var);
+            leaves.env_vars.push(env_var);
+            leaf_content
+        } else if app.graphql_documents.len() < self.graphql_import_count {
+            self.write_graphql_leaf(app, file)?
+        } else if app.json_files.len() < self.json_import_count {
+            self.write_json_leaf(app, file)?
+        } else if app.deep_expressions.len() < self.deep_expression_count {
+            app.deep_expressions.push(file.to_path_buf());
+            deep_expression_leaf(self.deep_expression_depth)
+        } else if leaves.diamonds < 2 * self.diamond_count {
+            self.write_diamond_leaf(app, leaves, file)?
+        } else if app.portals.len() < self.portal_count {
+            app.portals.push(file.to_path_buf());
+            portal_leaf(app.portals.len() - 1)
+        } else if self.tailwind && leaves.count % TAILWIND_CLASS_INTERVAL == 0 {
+            TAILWIND_LEAF.to_string()
+        } else if self.styled_components && leaves.count % STYLED_COMPONENTS_INTERVAL == 0 {
+            STYLED_COMPONENTS_LEAF.to_string()
+        } else if self.scss
+            && self.framework.uses_react_templates()
+            && leaves.count % SCSS_INTERVAL == 0
+        {
+            self.write_scss_leaf(app, leaves, file)?
+        } else if app.css_files.len() < self.css_file_count {
+            self.write_css_leaf(app, leaves, file)?
+        } else if !leaves.real_dependencies.is_empty()
+            && self.framework.uses_react_templates()
+            && leaves.count % REAL_DEPENDENCY_IMPORT_INTERVAL == 0
+        {
+            let specifier = &leaves.real_dependencies
+                [leaves.real_dependency_imports % leaves.real_dependencies.len()];
+            leaves.real_dependency_imports += 1;
+            real_dependency_leaf(specifier)
+        } else if self.subpath_exports
+            && self.framework.uses_react_templates()
+            && leaves.count % SUBPATH_IMPORT_INTERVAL == 0
+        {
+            subpath_leaf()
+        } else if self.css_theme_vars > 0 && leaves.count % THEME_CLASS_INTERVAL == 0 {
+            let classes = (self.css_theme_vars - 1) / THEME_VAR_USE_INTERVAL + 1;
+            let theme = leaves.theme_classes % classes * THEME_VAR_USE_INTERVAL;
+            leaves.theme_classes += 1;
+            theme_leaf(theme)
+        } else {
+            LEAF.to_string()
+        };
+        if self.unit_tests
+            && self.framework.uses_react_templates()
+            && (leaves.count - 1) % UNIT_TEST_INTERVAL == 0
+        {
+            self.write_unit_test(app, file)?;
+        }
+        let leaf_content = if app.named_exports.contains(file) {
+            into_named_export(leaf_content)
+        } else {
+            leaf_content
+        };
+        let leaf_content = if self.framework == Framework::Vue {
+            self.finish_vue_component(leaf_content)
+        } else {
+            self.finish_module(leaf_content)
+        };
+        File::create(file)
+            .context("creating file")?
+            .write_all(leaf_content.as_bytes())
+            .context("writing file")?;
+        Ok(())
+    }
+
+    /// Writes the `.graphql` query document of `graphql_import_count`
+    /// imported by the leaf module `file`, and returns the module.
+    fn write_graphql_leaf(&self, app: &mut TestApp, file: &Path) -> Result<String> {
+        let n = app.graphql_documents.len();
+        let document = file.with_extension("graphql");
+        File::create(&document)
+            .context("creating graphql document")?
+            .write_all(
+                format!(
+                    r#"query Triangle{n} {{
+    triangle(id: {n}) {{
+        id
+        points
+    }}
+}}
+"#
+                )
+                .as_bytes(),
+            )
+            .context("writing graphql document")?;
+        let document_import = relative_import(file, &document);
+        app.graphql_documents.push(document);
+        Ok(format!(
+            r#"import React from "react";
+import query from "{document_import}";
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}}>
+        <title>{{query.definitions[0].name.value}}</title>
+    </polygon>;
+}}
+
+export default React.memo(Triangle);
+"#
+        ))
+    }
+
+    /// Writes the `.json` data file of `json_import_count` imported by the
+    /// leaf module `file`, and returns the module.
+    fn write_json_leaf(&self, app: &mut TestApp, file: &Path) -> Result<String> {
+        let n = app.json_files.len();
+        let json_file = file.with_extension("json");
+        File::create(&json_file)
+            .context("creating json file")?
+            .write_all(
+                format!("{:#}", json!({ "id": n, "name": format!("Triangle {n}") })).as_bytes(),
+            )
+            .context("writing json file")?;
+        let json_import = relative_import(file, &json_file);
+        let import_attributes = if self.json_import_attributes {
+            r#" with { type: "json" }"#
+        } else {
+            ""
+        };
+        app.json_files.push(json_file);
+        Ok(format!(
+            r#"import React from "react";
+import data from "{json_import}"{import_attributes};
+
+function Triangle({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}}>
+        <title>{{data.name}}</title>
+    </polygon>;
+}}
+
+export default React.memo(Triangle);
+"#
+        ))
+    }
+
+    /// Returns a leaf module of `diamond_count` importing a shared module in
+    /// `src/diamonds`. The first leaf module of each pair writes the shared
+    /// module.
+    fn write_diamond_leaf(
+        &self,
+        app: &mut TestApp,
+        leaves: &mut Leaves,
+        file: &Path,
+    ) -> Result<String> {
+        let diamonds_dir = leaves.src.join("diamonds");
+        let shared_file = diamonds_dir.join(format!("shared_{}.jsx", leaves.diamonds / 2));
+        if leaves.diamonds % 2 == 0 {
+            create_dir_all(&diamonds_dir).context("creating diamonds dir")?;
+            let shared = r#"import React from "react";
+
+export default function Shared() {
+    return <circle r="0.5" style={{ fill: "black" }} />;
+}
+"#;
+            File::create(&shared_file)
+                .context("creating shared module")?
+                .write_all(shared.as_bytes())
+                .context("writing shared module")?;
+            app.diamonds.push(shared_file.clone());
+        }
+        leaves.diamonds += 1;
+        Ok(format!(
+            r#"import React from "react";
+import Shared from "{}";
+
+function Triangle({{ style }}) {{
+    return <g>
+        <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />
+        <Shared />
+    </g>;
+}}
+
+export default React.memo(Triangle);
+"#,
+            relative_import(file, &shared_file)
+        ))
+    }
+
+    /// Writes the `.scss` file of `scss` imported by the leaf module `file`,
+    /// and returns the module.
+    fn write_scss_leaf(&self, app: &mut TestApp, leaves: &Leaves, file: &Path) -> Result<String> {
+        let scss_file = file.with_extension("scss");
+        let class_name = format!("triangle-scss-{}", leaves.count);
+        let scss = format!(
+            r#"@use "{}" as mixins;
+
+$color: hsl({}, 70%, 60%);
+
+.{class_name} {{
+    @include mixins.interactive;
+
+    polygon {{
+        @include mixins.tinted($color);
+
+        &:hover {{
+            stroke-width: 0.2;
+        }}
+    }}
+}}
+"#,
+            relative_import(&scss_file, &leaves.src.join("styles/_mixins.scss")),
+            leaves.count * 37 % 360
+        );
+        File::create(&scss_file)
+            .context("creating scss file")?
+            .write_all(scss.as_bytes())
+            .context("writing scss file")?;
+        let content = format!(
+            r#"import React from "react";
+import "./{}";
+
+function Triangle({{ style }}) {{
+    return <g className="{class_name}">
+        <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />
+    </g>;
+}}
+
+export default React.memo(Triangle);
+"#,
+            scss_file.file_name().unwrap().to_str().unwrap()
+        );
+        app.scss_files.push(scss_file);
+        Ok(content)
+    }
+
+    /// Writes the stylesheet of `css_file_count` imported by the leaf module
+    /// `file`, either as a CSS module or for its side effects, and returns the
+    /// module.
+    fn write_css_leaf(
+        &self,
+        app: &mut TestApp,
+        leaves: &mut Leaves,
+        file: &Path,
+    ) -> Result<String> {
+        let n = app.css_files.len();
+        let css_module = (leaves.css_modules as f32) < self.css_module_ratio * (n + 1) as f32;
+        let (css_file, class_name, import, class_name_expression) = if css_module {
+            leaves.css_modules += 1;
+            (
+                file.with_extension("module.css"),
+                "triangle".to_string(),
+                "import styles from",
+                "{styles.triangle}".to_string(),
+            )
+        } else {
+            let class_name = format!("triangle-css-{n}");
+            (
+                file.with_extension("css"),
+                class_name.clone(),
+                "import",
+                format!("\"{class_name}\""),
+            )
+        };
+        let css = format!(
+            r#".{class_name} polygon {{
+    fill: hsl({}, 70%, 60%);
+}}
+"#,
+            n * 37 % 360
+        );
+        File::create(&css_file)
+            .context("creating css file")?
+            .write_all(css.as_bytes())
+            .context("writing css file")?;
+        let content = format!(
+            r#"import React from "react";
+{import} "./{}";
+
+function Triangle({{ style }}) {{
+    return <g className={class_name_expression}>
+        <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />
+    </g>;
+}}
+
+export default React.memo(Triangle);
+"#,
+            css_file.file_name().unwrap().to_str().unwrap()
+        );
+        app.css_files.push(css_file);
+        Ok(content)
+    }
+
+    /// Writes the snapshot test of `unit_tests` next to the leaf module
+    /// `file`.
+    fn write_unit_test(&self, app: &mut TestApp, file: &Path) -> Result<()> {
+        let name = file.file_stem().unwrap().to_str().unwrap();
+        let unit_test = file.with_extension("test.jsx");
+        let extension = file.extension().unwrap().to_str().unwrap();
+        let import_triangle = if app.named_exports.contains(file) {
+            "import { Triangle }"
+        } else {
+            "import Triangle"
+        };
+        let unit_test_content = format!(
+            r#"import React from "react";
+import {{ render }} from "@testing-library/react";
+{import_triangle} from "./{name}.{extension}";
+
+test("renders {name}", () => {{
+    const {{ container }} = render(<svg><Triangle style={{{{ fill: "white" }}}} /></svg>);
+    expect(container.querySelector("polygon")).not.toBeNull();
+    expect(container).toMatchSnapshot();
+}});
+"#
+        );
+        File::create(&unit_test)
+            .context("creating unit test")?
+            .write_all(unit_test_content.as_bytes())
+            .context("writing unit test")?;
+        app.unit_tests.push(unit_test);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs::read_to_string};
+
+    use serde_json::Value;
+
+    use crate::test_app_builder::{
+        relative_import, test_app::relative_import_specifiers, PackageJsonConfig, TestAppBuilder,
+    };
+
+    #[test]
+    fn import_meta_env_reads_are_defined_in_dotenv() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            import_meta_env_count: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let dotenv = read_to_string(app.path().join(".env")).unwrap();
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        let readers = modules
+            .iter()
+            .filter_map(|module| {
+                let start = module.find("import.meta.env.")? + "import.meta.env.".len();
+                let end = start + module[start..].find('}')?;
+                Some(&module[start..end])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(readers.len(), 3);
+        for env_var in readers {
+            assert!(env_var.starts_with("VITE_"));
+            assert!(dotenv
+                .lines()
+                .any(|line| line.starts_with(&format!("{env_var}="))));
+        }
+    }
+
+    #[test]
+    fn graphql_documents_are_generated_and_imported() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            graphql_import_count: 4,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.graphql_documents().len(), 4);
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        for document in app.graphql_documents() {
+            assert!(read_to_string(document).unwrap().starts_with("query "));
+            let specifier = format!("/{}\"", document.file_name().unwrap().to_str().unwrap());
+            assert!(modules
+                .iter()
+                .any(|module| module.contains("import query from") && module.contains(&specifier)));
+        }
+    }
+
+    #[test]
+    fn error_boundaries_wrap_subtrees() {
+        let app = TestAppBuilder {
+            module_count: 200,
+            error_boundaries: 4,
+            error_boundary_throw: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.error_boundaries().len(), 4);
+        for boundary in app.error_boundaries() {
+            let content = read_to_string(boundary).unwrap();
+            assert!(content.contains(" extends React.Component {"));
+            assert!(content.contains("    componentDidCatch(error, info) {"));
+        }
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modules
+                .iter()
+                .filter(|module| module.contains("<ErrorBoundary style={style}>"))
+                .count(),
+            4
+        );
+        assert_eq!(
+            modules
+                .iter()
+                .filter(|module| module.contains("<Thrower />"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn real_dependencies_are_imported_by_leaves() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            resolve_real_deps: true,
+            package_json: Some(PackageJsonConfig {
+                extra_dependencies: [
+                    ("lodash".to_string(), "^4.17.21".to_string()),
+                    ("@emotion/react".to_string(), "^11.10.5".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package_json["dependencies"]["lodash"], "^4.17.21");
+        assert_eq!(package_json["dependencies"]["@emotion/react"], "^11.10.5");
+        let specifiers = app
+            .modules()
+            .iter()
+            .filter_map(|(module, _)| {
+                let content = read_to_string(module).unwrap();
+                let start = content.find("import * as dependency from \"")? + 29;
+                let end = start + content[start..].find('"')?;
+                Some(content[start..end].to_string())
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            specifiers,
+            HashSet::from([
+                "lodash/fp/compose".to_string(),
+                "@emotion/react".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn css_files_are_imported_as_modules_and_for_side_effects() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            css_file_count: 6,
+            css_module_ratio: 0.5,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.css_files().len(), 6);
+        let mut css_modules = 0;
+        for css_file in app.css_files() {
+            let name = css_file.file_name().unwrap().to_str().unwrap();
+            let stem = name.split('.').next().unwrap();
+            let module = css_file.with_file_name(format!("{stem}.jsx"));
+            let content = read_to_string(module).unwrap();
+            let css = read_to_string(css_file).unwrap();
+            if name.ends_with(".module.css") {
+                css_modules += 1;
+                assert!(content.contains(&format!("import styles from \"./{name}\";")));
+                assert!(content.contains("className={styles.triangle}"));
+                assert!(css.starts_with(".triangle polygon {"));
+            } else {
+                assert!(content.contains(&format!("import \"./{name}\";")));
+                assert!(css.starts_with(".triangle-css-"));
+            }
+        }
+        assert_eq!(css_modules, 3);
+    }
+
+    #[test]
+    fn deep_expressions_nest_to_the_configured_depth() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            deep_expression_count: 2,
+            deep_expression_depth: 30,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.deep_expressions().len(), 2);
+        for module in app.deep_expressions() {
+            let content = read_to_string(module).unwrap();
+            let expression = content
+                .split_once("    return ")
+                .unwrap()
+                .1
+                .split_once(";\n}")
+                .unwrap()
+                .0;
+            assert_eq!(expression.matches("(level === ").count(), 30);
+            assert_eq!(expression.matches('?').count(), 30);
+            // The parentheses are balanced and nest 30 levels deep.
+            let mut depth = 0;
+            let mut max_depth = 0;
+            for c in expression.chars() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                assert!(depth >= 0);
+                max_depth = max_depth.max(depth);
+            }
+            assert_eq!(depth, 0);
+            assert_eq!(max_depth, 30);
+            assert!(expression.ends_with(&format!(" />{}", ")".repeat(30))));
+        }
+    }
+
+    #[test]
+    fn counted_leaf_features_take_the_next_free_leaves() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            graphql_import_count: 3,
+            json_import_count: 3,
+            diamond_count: 2,
+            portal_count: 3,
+            css_file_count: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.graphql_documents().len(), 3);
+        assert_eq!(app.json_files().len(), 3);
+        assert_eq!(app.diamonds().len(), 2);
+        assert_eq!(app.portals().len(), 3);
+        assert_eq!(app.css_files().len(), 3);
+        let mut leaves = app.graphql_documents().to_vec();
+        leaves.extend(app.json_files().iter().cloned());
+        leaves.extend(app.css_files().iter().cloned());
+        let leaves = leaves
+            .iter()
+            .map(|file| file.with_extension("").with_extension(""))
+            .chain(app.portals().iter().map(|file| file.with_extension("")))
+            .collect::<HashSet<_>>();
+        // Each leaf module uses at most one leaf feature.
+        assert_eq!(leaves.len(), 12);
+    }
+
+    #[test]
+    fn diamonds_share_a_module_between_two_leaves() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            diamond_count: 5,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.diamonds().len(), 5);
+        assert_eq!(app.verify().unwrap(), vec![]);
+        for shared in app.diamonds() {
+            // The shared module is a leaf, so it can't be part of a cycle.
+            assert!(relative_import_specifiers(&read_to_string(shared).unwrap()).is_empty());
+            let importers = app
+                .modules()
+                .iter()
+                .filter(|(module, _)| {
+                    read_to_string(module).unwrap().contains(&format!(
+                        "import Shared from \"{}\";",
+                        relative_import(module, shared)
+                    ))
+                })
+                .count();
+            // Two leaves of the tree always have a common ancestor that reaches
+            // the shared module via two paths.
+            assert_eq!(importers, 2);
+        }
+    }
+
+    #[test]
+    fn portals_are_created_on_the_client() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            portal_count: 4,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.portals().len(), 4);
+        let portals = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .filter(|content| content.contains("createPortal("))
+            .collect::<Vec<_>>();
+        assert_eq!(portals.len(), 4);
+        for content in portals {
+            assert_eq!(content.matches("createPortal(").count(), 1);
+            assert!(content.contains("if (typeof document === \"undefined\") {"));
+            assert!(content.contains("document.body.appendChild(target);"));
+        }
+    }
+
+    #[test]
+    fn styled_components_are_used() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            styled_components: true,
+            package_json: Some(Default::default()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert!(app.modules().iter().any(|(module, _)| {
+            let content = read_to_string(module).unwrap();
+            content.contains("import styled from \"styled-components\";")
+                && content.contains("styled.polygon`")
+        }));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["styled-components"].is_string());
+    }
+
+    #[test]
+    fn subpath_exports_are_imported_by_components() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            subpath_exports: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let shared = app.path().join("packages/shared");
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(shared.join("package.json")).unwrap()).unwrap();
+        let exports = package_json["exports"].as_object().unwrap();
+        assert!(exports.len() > 1);
+        assert!(exports["./sizes"].is_object());
+        assert!(package_json["imports"]["#internal/*"].is_string());
+        assert!(read_to_string(shared.join("src/colors.js"))
+            .unwrap()
+            .contains("from \"#internal/palette\""));
+        assert!(app
+            .modules()
+            .iter()
+            .any(|(module, _)| read_to_string(module)
+                .unwrap()
+                .contains("from \"@turbopack-test-app/shared/colors\";")));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            package_json["workspaces"],
+            serde_json::json!(["packages/*"])
+        );
+        assert!(package_json["dependencies"]["@turbopack-test-app/shared"].is_string());
+    }
+
+    #[test]
+    fn unit_tests_render_their_component() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            unit_tests: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert!(!app.unit_tests().is_empty());
+        for unit_test in app.unit_tests() {
+            let content = read_to_string(unit_test).unwrap();
+            let file_name = unit_test.file_name().unwrap().to_str().unwrap();
+            let component = file_name.strip_suffix(".test.jsx").unwrap();
+            assert!(unit_test
+                .with_file_name(format!("{component}.jsx"))
+                .exists());
+            assert!(content.contains(&format!("import Triangle from \"./{component}.jsx\";")));
+            assert!(content.contains("render(<svg><Triangle"));
+            assert!(content.contains("toMatchSnapshot()"));
+        }
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["devDependencies"]["@testing-library/react"].is_string());
+    }
+
+    #[test]
+    fn json_imports_use_import_attributes() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            json_import_count: 3,
+            json_import_attributes: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.json_files().len(), 3);
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        for json_file in app.json_files() {
+            let import = format!(
+                "/{}\" with {{ type: \"json\" }};",
+                json_file.file_name().unwrap().to_str().unwrap()
+            );
+            assert!(modules.iter().any(|module| module.contains(&import)));
+        }
+    }
+
+    #[test]
+    fn forward_refs_match_ratio_and_receive_refs() {
+        let app = TestAppBuilder {
+            module_count: 200,
+            dynamic_import_count: 10,
+            forward_ref_ratio: 0.3,
+            named_export_ratio: 0.25,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        let forward_refs = modules
+            .iter()
+            .filter(|module| module.contains("React.forwardRef(function "))
+            .count();
+        let expected = 0.3 * (modules.len() - 1) as f32;
+        assert!((forward_refs as f32 - expected).abs() <= 1.0);
+        assert!(!modules[0].contains("React.forwardRef("));
+        assert!(modules
+            .iter()
+            .any(|module| module.contains("React.useImperativeHandle(ref, () => ({")));
+
+        // Every forwarding module is passed a ref created by its parent.
+        let passed_refs = modules
+            .iter()
+            .map(|module| {
+                ["A", "B", "C"]
+                    .into_iter()
+                    .filter(|name| module.contains(&format!(" ref={{ref{name}}}")))
+                    .inspect(|name| {
+                        assert!(module.contains(&format!("const ref{name} = React.useRef(null);")))
+                    })
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(passed_refs, forward_refs);
+    }
+}
@@ -0,0 +1,1230 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::prelude::*,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::{
+    custom_element_name, node_builtin_imports, relative_import, test_app::TestApp, TestAppBuilder,
+};
+
+/// Writes the `src/detector.jsx` component rendered by the root module of the
+/// React templates.
+fn write_detector(src: &Path) -> Result<()> {
+    // The component is used to measure hydration and commit time for app/page.jsx
+    let detector_component = r#""use client";
+
+import React from "react";
+
+export default function Detector({ message }) {
+    React.useEffect(() => {
+        globalThis.__turbopackBenchBinding && globalThis.__turbopackBenchBinding("Hydration done");
+    });
+    React.useEffect(() => {
+        message && globalThis.__turbopackBenchBinding && globalThis.__turbopackBenchBinding(message);
+    }, [message]);
+    return null;
+}
+"#;
+    File::create(src.join("detector.jsx"))
+        .context("creating detector component")?
+        .write_all(detector_component.as_bytes())
+        .context("writing detector component")?;
+    Ok(())
+}
+
+impl TestAppBuilder {
+    /// Writes the entries using the React templates, which are used by the
+    /// different bundlers, e.g. the bootstrap, the Next.js pages and the HTML
+    /// files.
+    pub(super) fn write_react_entries(&self, app: &mut TestApp, css_import: &str) -> Result<()> {
+        let path = app.path().to_path_buf();
+        let src = path.join("src");
+
+        let bootstrap = r#"import React from "react";
+import { createRoot } from "react-dom/client";
+import Triangle from "./triangle.jsx";
+
+function App() {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ }}>
+        <Triangle style={{ fill: "white" }}/>
+    </svg>
+}
+
+document.body.style.backgroundColor = "black";
+let root = document.createElement("main");
+document.body.appendChild(root);
+createRoot(root).render(<App />);
+"#;
+        let decorators_import = if self.decorators > 0 {
+            self.write_decorated_modules(app)?;
+            "import \"./decorators/index.ts\";\n"
+        } else {
+            ""
+        };
+        let types_import = if self.type_only_import_count > 0 {
+            self.write_type_only_imports(app)?;
+            "import \"./types/index.ts\";\n"
+        } else {
+            ""
+        };
+        let dynamic_import = if self.dynamic_expression_count > 0 {
+            self.write_dynamic_expressions(app)?;
+            "import \"./dynamic/index.js\";\n"
+        } else {
+            ""
+        };
+        let workers_import = if self.shared_worker_count > 0 || self.audio_worklet_count > 0 {
+            self.write_workers(app)?;
+            "import \"./workers/index.js\";\n"
+        } else {
+            ""
+        };
+        File::create(src.join("index.jsx"))
+            .context("creating bootstrap file")?
+            .write_all(
+                format!(
+                    "{css_import}{decorators_import}{types_import}{dynamic_import}{workers_import}\
+                     {bootstrap}"
+                )
+                .as_bytes(),
+            )
+            .context("writing bootstrap file")?;
+
+        let pages = src.join("pages");
+        create_dir_all(&pages)?;
+
+        // The page is e. g. used by Next.js
+        let bootstrap_page = r#"import React from "react";
+import Triangle from "../triangle.jsx";
+
+export default function Page() {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ backgroundColor: "black" }}>
+        <Triangle style={{ fill: "white" }}/>
+    </svg>
+}
+"#;
+        File::create(pages.join("page.jsx"))
+            .context("creating bootstrap page")?
+            .write_all(bootstrap_page.as_bytes())
+            .context("writing bootstrap page")?;
+
+        // The page is e. g. used by Next.js
+        let bootstrap_static_page = r#"import React from "react";
+import Triangle from "../triangle.jsx";
+
+export default function Page() {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ backgroundColor: "black" }}>
+        <Triangle style={{ fill: "white" }}/>
+    </svg>
+}
+
+export function getStaticProps() {
+    return {
+        props: {}
+    };
+}
+"#;
+        File::create(pages.join("static.jsx"))
+            .context("creating bootstrap static page")?
+            .write_all(bootstrap_static_page.as_bytes())
+            .context("writing bootstrap static page")?;
+
+        if self.node_builtin_imports > 0 {
+            let api = pages.join("api");
+            create_dir_all(&api).context("creating api dir")?;
+            let (imports, usage) = node_builtin_imports(self.node_builtin_imports);
+            let api_route = format!(
+                r#"{imports}
+export default function handler(req, res) {{
+    {usage}    res.status(200).json({{ builtins: builtins.length }});
+}}
+"#
+            );
+            File::create(api.join("builtins.js"))
+                .context("creating api route")?
+                .write_all(api_route.as_bytes())
+                .context("writing api route")?;
+        }
+
+        for i in 0..self.pages_router_page_count {
+            // Spread the pages over the tree so that each renders a different
+            // component.
+            let (component, _) = &app.modules[i * app.modules.len() / self.pages_router_page_count];
+            let page = pages.join(format!("page_{}.jsx", i + 1));
+            let component_import = relative_import(&page, component);
+            let import_component = if app.named_exports.contains(component) {
+                "import { Triangle as Component }"
+            } else {
+                "import Component"
+            };
+            let page_content = format!(
+                r#"import React from "react";
+{import_component} from "{component_import}";
+
+export default function Page() {{
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{{{ backgroundColor: "black" }}}}>
+        <Component style={{{{ fill: "white" }}}}/>
+    </svg>
+}}
+"#
+            );
+            File::create(&page)
+                .context("creating pages router page")?
+                .write_all(page_content.as_bytes())
+                .context("writing pages router page")?;
+            app.pages.push(page);
+        }
+
+        let app_dir = src.join("app");
+        create_dir_all(app_dir.join("app"))?;
+        create_dir_all(app_dir.join("client"))?;
+
+        // The page is e. g. used by Next.js
+        let bootstrap_app_page = r#"import React from "react";
+import Triangle from "../../triangle.jsx";
+
+export default function Page() {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ backgroundColor: "black" }}>
+        <Triangle style={{ fill: "white" }}/>
+    </svg>
+}
+"#;
+        File::create(app_dir.join("app/page.jsx"))
+            .context("creating bootstrap app page")?
+            .write_all(bootstrap_app_page.as_bytes())
+            .context("writing bootstrap app page")?;
+
+        write_detector(&src)?;
+
+        // The page is e. g. used by Next.js
+        let bootstrap_app_client_page = r#""use client";
+import React from "react";
+import Triangle from "../../triangle.jsx";
+
+export default function Page() {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ backgroundColor: "black" }}>
+        <Triangle style={{ fill: "white" }}/>
+    </svg>
+}
+"#;
+        File::create(app_dir.join("client/page.jsx"))
+            .context("creating bootstrap app client page")?
+            .write_all(bootstrap_app_client_page.as_bytes())
+            .context("writing bootstrap app client page")?;
+
+        if self.server_actions > 0 {
+            let forms_dir = app_dir.join("forms");
+            create_dir_all(&forms_dir).context("creating forms dir")?;
+
+            let mut actions = "\"use server\";\n".to_string();
+            for i in 0..self.server_actions {
+                actions.push_str(&format!(
+                    r#"
+export async function submitTriangle{i}(formData) {{
+    const color = formData.get("color");
+    console.log(`Triangle {i} submitted with ${{color}}`);
+}}
+"#
+                ));
+            }
+            File::create(forms_dir.join("actions.js"))
+                .context("creating server actions")?
+                .write_all(actions.as_bytes())
+                .context("writing server actions")?;
+
+            let names = (0..self.server_actions)
+                .map(|i| format!("submitTriangle{i}"))
+                .collect::<Vec<_>>();
+            let forms = (0..self.server_actions)
+                .map(|i| {
+                    format!(
+                        r#"
+            <form action={{submitTriangle{i}}}>
+                <input name="color" defaultValue="white" />
+                <button type="submit">Submit triangle {i}</button>
+            </form>"#
+                    )
+                })
+                .collect::<String>();
+            let forms_page = format!(
+                r#"import React from "react";
+import {{ {} }} from "./actions.js";
+
+export default function Page() {{
+    return (
+        <main>{forms}
+        </main>
+    );
+}}
+"#,
+                names.join(", ")
+            );
+            File::create(forms_dir.join("page.jsx"))
+                .context("creating forms page")?
+                .write_all(forms_page.as_bytes())
+                .context("writing forms page")?;
+        }
+
+        let mut nested_dir = app_dir.join("nested");
+        for i in 0..self.nested_layouts {
+            nested_dir.push(format!("level_{}", i + 1));
+            create_dir_all(&nested_dir).context("creating nested layout dir")?;
+            let layout = nested_dir.join("layout.jsx");
+            let (module, _) = &app.modules[i % app.modules.len()];
+            let import_triangle = if app.named_exports.contains(module) {
+                "import { Triangle }"
+            } else {
+                "import Triangle"
+            };
+            let content = format!(
+                r#"import React from "react";
+{import_triangle} from "{}";
+
+export default function Level{}Layout({{ children }}) {{
+    return (
+        <section>
+            <svg height="10%" viewBox="-5 -4.33 10 8.66" style={{{{ backgroundColor: "black" }}}}>
+                <Triangle style={{{{ fill: "white" }}}} />
+            </svg>
+            {{children}}
+        </section>
+    );
+}}
+"#,
+                relative_import(&layout, module),
+                i + 1
+            );
+            File::create(&layout)
+                .context("creating nested layout")?
+                .write_all(content.as_bytes())
+                .context("writing nested layout")?;
+            app.nested_layouts.push(layout);
+        }
+        if self.nested_layouts > 0 {
+            let page = r#"import React from "react";
+
+export default function Page() {
+    return <p>Nested layouts</p>;
+}
+"#;
+            File::create(nested_dir.join("page.jsx"))
+                .context("creating nested page")?
+                .write_all(page.as_bytes())
+                .context("writing nested page")?;
+        }
+
+        for (i, group) in self.route_groups.iter().enumerate() {
+            if group.is_empty() || group.contains(['/', '\\', '(', ')']) {
+                bail!("invalid route group name {group:?}");
+            }
+            let group_dir = app_dir.join(format!("({group})"));
+            let page_dir = group_dir.join(format!("group_{i}"));
+            create_dir_all(&page_dir).context("creating route group dir")?;
+            let (module, _) = &app.modules[i % app.modules.len()];
+            let import_triangle = if app.named_exports.contains(module) {
+                "import { Triangle }"
+            } else {
+                "import Triangle"
+            };
+
+            if i % 2 == 0 {
+                let layout = group_dir.join("layout.jsx");
+                let content = format!(
+                    r#"import React from "react";
+{import_triangle} from "{}";
+
+export default function GroupLayout{i}({{ children }}) {{
+    return (
+        <div data-group="{group}">
+            <svg height="10%" viewBox="-5 -4.33 10 8.66" style={{{{ backgroundColor: "black" }}}}>
+                <Triangle style={{{{ fill: "white" }}}} />
+            </svg>
+            {{children}}
+        </div>
+    );
+}}
+"#,
+                    relative_import(&layout, module)
+                );
+                File::create(&layout)
+                    .context("creating route group layout")?
+                    .write_all(content.as_bytes())
+                    .context("writing route group layout")?;
+            }
+
+            let page = page_dir.join("page.jsx");
+            let content = format!(
+                r#"import React from "react";
+{import_triangle} from "{}";
+
+export default function GroupPage{i}() {{
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{{{ backgroundColor: "black" }}}}>
+        <Triangle style={{{{ fill: "white" }}}} />
+    </svg>
+}}
+"#,
+                relative_import(&page, module)
+            );
+            File::create(&page)
+                .context("creating route group page")?
+                .write_all(content.as_bytes())
+                .context("writing route group page")?;
+            app.route_groups.push(group_dir);
+        }
+
+        if self.metadata_routes {
+            self.write_metadata_routes(app, &app_dir)?;
+        }
+
+        // This root layout is e. g. used by Next.js
+        let bootstrap_layout = r#"export default function RootLayout({ children }) {
+    return (
+        <html lang="en">
+            <head>
+                <meta charSet="UTF-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+                <title>Turbopack Test App</title>
+            </head>
+            <body>
+                {children}
+            </body>
+        </html>
+    );
+}
+        "#;
+        File::create(app_dir.join("layout.jsx"))
+            .context("creating bootstrap html in root")?
+            .write_all(bootstrap_layout.as_bytes())
+            .context("writing bootstrap html in root")?;
+
+        // This HTML is used e. g. by Vite
+        let bootstrap_html = r#"<!DOCTYPE html>
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body>
+        <script type="module" src="/src/index.jsx"></script>
+    </body>
+</html>
+"#;
+        File::create(path.join("index.html"))
+            .context("creating bootstrap html in root")?
+            .write_all(bootstrap_html.as_bytes())
+            .context("writing bootstrap html in root")?;
+
+        // This HTML is used e. g. by webpack
+        let bootstrap_html2 = r#"<!DOCTYPE html>
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body>
+        <script src="main.js"></script>
+    </body>
+</html>
+"#;
+
+        let public = path.join("public");
+        create_dir_all(&public).context("creating public dir")?;
+
+        File::create(public.join("index.html"))
+            .context("creating bootstrap html in public")?
+            .write_all(bootstrap_html2.as_bytes())
+            .context("writing bootstrap html in public")?;
+
+        Ok(())
+    }
+
+    /// Writes the Remix root, a route module for the root module and each of
+    /// its children, and the Remix config. The components stay in `src`.
+    pub(super) fn write_remix_entries(&self, app: &TestApp, css_import: &str) -> Result<()> {
+        let path = app.path();
+        let app_dir = path.join("app");
+        let routes = app_dir.join("routes");
+        create_dir_all(&routes).context("creating routes dir")?;
+
+        write_detector(&path.join("src"))?;
+
+        // The CSS imports are relative to `src`.
+        let css_import = css_import.replace("import \"./", "import \"../src/");
+        let root = format!(
+            r#"{css_import}import React from "react";
+import {{ Links, LiveReload, Meta, Outlet, Scripts }} from "@remix-run/react";
+
+export default function App() {{
+    return (
+        <html lang="en">
+            <head>
+                <meta charSet="UTF-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+                <title>Turbopack Test App</title>
+                <Meta />
+                <Links />
+            </head>
+            <body style={{{{ backgroundColor: "black" }}}}>
+                <Outlet />
+                <Scripts />
+                <LiveReload />
+            </body>
+        </html>
+    );
+}}
+"#
+        );
+        File::create(app_dir.join("root.jsx"))
+            .context("creating remix root")?
+            .write_all(root.as_bytes())
+            .context("writing remix root")?;
+
+        let route_components = app
+            .modules
+            .iter()
+            .filter(|(_, depth)| *depth <= 1)
+            .enumerate();
+        for (i, (component, _)) in route_components {
+            let route = if i == 0 {
+                routes.join("_index.jsx")
+            } else {
+                routes.join(format!("subtree_{i}.jsx"))
+            };
+            let component_import = relative_import(&route, component);
+            let (builtin_imports, builtin_usage) = if self.node_builtin_imports > 0 {
+                let (imports, usage) = node_builtin_imports(self.node_builtin_imports);
+                (imports, format!("{usage}    "))
+            } else {
+                Default::default()
+            };
+            let import_component = if app.named_exports.contains(component) {
+                "import { Triangle as Component }"
+            } else {
+                "import Component"
+            };
+            let route_content = format!(
+                r#"import React from "react";
+import {{ json }} from "@remix-run/node";
+import {{ useLoaderData }} from "@remix-run/react";
+{import_component} from "{component_import}";
+{builtin_imports}
+export async function loader() {{
+    {builtin_usage}return json({{ fill: "white" }});
+}}
+
+export default function Route() {{
+    const {{ fill }} = useLoaderData();
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66">
+        <Component style={{{{ fill }}}} />
+    </svg>;
+}}
+"#
+            );
+            File::create(&route)
+                .context("creating remix route")?
+                .write_all(route_content.as_bytes())
+                .context("writing remix route")?;
+        }
+
+        let remix_config = r#"/** @type {import('@remix-run/dev').AppConfig} */
+module.exports = {
+    appDirectory: "app",
+    ignoredRouteFiles: ["**/.*"],
+};
+"#;
+        File::create(path.join("remix.config.js"))
+            .context("creating remix config")?
+            .write_all(remix_config.as_bytes())
+            .context("writing remix config")?;
+
+        Ok(())
+    }
+
+    /// Writes the bootstrap appending the root custom element of
+    /// [Framework::WebComponents](super::Framework::WebComponents), together
+    /// with the HTML used by Vite.
+    pub(super) fn write_web_components_entries(
+        &self,
+        app: &TestApp,
+        css_import: &str,
+    ) -> Result<()> {
+        let path = app.path();
+        let src = path.join("src");
+
+        let (root, _) = &app.modules[0];
+        let name = custom_element_name(&src, root);
+        let bootstrap = format!(
+            r#"{css_import}import "./triangle.js";
+
+document.body.style.backgroundColor = "black";
+const root = document.createElement("{name}");
+root.setAttribute("fill", "white");
+root.style.width = "100vmin";
+root.style.height = "86.6vmin";
+document.body.appendChild(root);
+"#
+        );
+        File::create(src.join("index.js"))
+            .context("creating bootstrap file")?
+            .write_all(bootstrap.as_bytes())
+            .context("writing bootstrap file")?;
+
+        let bootstrap_html = r#"<!DOCTYPE html>
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body>
+        <script type="module" src="/src/index.js"></script>
+    </body>
+</html>
+"#;
+        File::create(path.join("index.html"))
+            .context("creating bootstrap html in root")?
+            .write_all(bootstrap_html.as_bytes())
+            .context("writing bootstrap html in root")?;
+
+        Ok(())
+    }
+
+    /// Writes the Vue bootstrap mounting the root component, together with the
+    /// HTML and the config used by Vite.
+    pub(super) fn write_vue_entries(&self, app: &TestApp, css_import: &str) -> Result<()> {
+        let path = app.path();
+        let src = path.join("src");
+
+        let main = if self.typescript {
+            "main.ts"
+        } else {
+            "main.js"
+        };
+        let bootstrap = format!(
+            r#"{css_import}import {{ createApp, h }} from "vue";
+import Triangle from "./triangle.vue";
+
+document.body.style.backgroundColor = "black";
+const root = document.createElement("main");
+document.body.appendChild(root);
+createApp({{
+    render: () =>
+        h("svg", {{ height: "100%", viewBox: "-5 -4.33 10 8.66" }}, [
+            h(Triangle, {{ style: {{ fill: "white" }} }}),
+        ]),
+}}).mount(root);
+"#
+        );
+        File::create(src.join(main))
+            .context("creating bootstrap file")?
+            .write_all(bootstrap.as_bytes())
+            .context("writing bootstrap file")?;
+
+        if self.typescript {
+            let shims = r#"declare module "*.vue" {
+    import type { DefineComponent } from "vue";
+    const component: DefineComponent<{}, {}, any>;
+    export default component;
+}
+"#;
+            File::create(src.join("shims-vue.d.ts"))
+                .context("creating vue shims")?
+                .write_all(shims.as_bytes())
+                .context("writing vue shims")?;
+        }
+
+        let bootstrap_html = format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body>
+        <script type="module" src="/src/{main}"></script>
+    </body>
+</html>
+"#
+        );
+        File::create(path.join("index.html"))
+            .context("creating bootstrap html in root")?
+            .write_all(bootstrap_html.as_bytes())
+            .context("writing bootstrap html in root")?;
+
+        let vite_config = r#"import { defineConfig } from "vite";
+import vue from "@vitejs/plugin-vue";
+
+export default defineConfig({
+    plugins: [vue()],
+});
+"#;
+        File::create(path.join("vite.config.js"))
+            .context("creating vite config")?
+            .write_all(vite_config.as_bytes())
+            .context("writing vite config")?;
+
+        Ok(())
+    }
+
+    /// Writes the SolidJS bootstrap together with the HTML and the config used
+    /// by Vite.
+    pub(super) fn write_solid_entries(&self, app: &TestApp, css_import: &str) -> Result<()> {
+        let path = app.path();
+        let src = path.join("src");
+
+        let bootstrap = format!(
+            r#"{css_import}import {{ render }} from "solid-js/web";
+import Triangle from "./triangle.jsx";
+
+function App() {{
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66">
+        <Triangle style={{{{ fill: "white" }}}} />
+    </svg>;
+}}
+
+document.body.style.backgroundColor = "black";
+let root = document.createElement("main");
+document.body.appendChild(root);
+render(() => <App />, root);
+"#
+        );
+        File::create(src.join("index.jsx"))
+            .context("creating bootstrap file")?
+            .write_all(bootstrap.as_bytes())
+            .context("writing bootstrap file")?;
+
+        let bootstrap_html = r#"<!DOCTYPE html>
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body>
+        <script type="module" src="/src/index.jsx"></script>
+    </body>
+</html>
+"#;
+        File::create(path.join("index.html"))
+            .context("creating bootstrap html in root")?
+            .write_all(bootstrap_html.as_bytes())
+            .context("writing bootstrap html in root")?;
+
+        let vite_config = r#"import { defineConfig } from "vite";
+import solid from "vite-plugin-solid";
+
+export default defineConfig({
+    plugins: [solid()],
+});
+"#;
+        File::create(path.join("vite.config.js"))
+            .context("creating vite config")?
+            .write_all(vite_config.as_bytes())
+            .context("writing vite config")?;
+
+        Ok(())
+    }
+
+    /// Writes the Astro index page rendering the root component and the Astro
+    /// config enabling the React integration for the islands.
+    pub(super) fn write_astro_entries(&self, app: &TestApp, css_import: &str) -> Result<()> {
+        let path = app.path();
+        let pages = path.join("src/pages");
+        create_dir_all(&pages).context("creating pages dir")?;
+
+        // The CSS imports are relative to `src`.
+        let css_import = css_import.replace("import \"./", "import \"../");
+        let index = format!(
+            r#"---
+{css_import}import Triangle from "../triangle.astro";
+---
+<html lang="en">
+    <head>
+        <meta charset="UTF-8" />
+        <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+        <title>Turbopack Test App</title>
+    </head>
+    <body style="background-color: black">
+        <svg height="100%" viewBox="-5 -4.33 10 8.66">
+            <Triangle style={{{{ fill: "white" }}}} />
+        </svg>
+    </body>
+</html>
+"#
+        );
+        File::create(pages.join("index.astro"))
+            .context("creating astro index page")?
+            .write_all(index.as_bytes())
+            .context("writing astro index page")?;
+
+        let astro_config = r#"import { defineConfig } from "astro/config";
+import react from "@astrojs/react";
+
+export default defineConfig({
+    integrations: [react()],
+});
+"#;
+        File::create(path.join("astro.config.mjs"))
+            .context("creating astro config")?
+            .write_all(astro_config.as_bytes())
+            .context("writing astro config")?;
+
+        Ok(())
+    }
+
+    /// Writes the Qwik City root component and the index route.
+    pub(super) fn write_qwik_entries(&self, app: &TestApp, css_import: &str) -> Result<()> {
+        let src = app.path().join("src");
+
+        let root = format!(
+            r#"import {{ component$ }} from "@builder.io/qwik";
+import {{ QwikCityProvider, RouterOutlet }} from "@builder.io/qwik-city";
+{css_import}
+export default component$(() => {{
+    return (
+        <QwikCityProvider>
+            <head>
+                <meta charSet="UTF-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+                <title>Turbopack Test App</title>
+            </head>
+            <body>
+                <RouterOutlet />
+            </body>
+        </QwikCityProvider>
+    );
+}});
+"#
+        );
+        File::create(src.join("root.tsx"))
+            .context("creating qwik root")?
+            .write_all(root.as_bytes())
+            .context("writing qwik root")?;
+
+        let routes = src.join("routes");
+        create_dir_all(&routes).context("creating routes dir")?;
+
+        let index_route = r#"import { component$ } from "@builder.io/qwik";
+import Triangle from "../triangle";
+
+export default component$(() => {
+    return <svg height="100%" viewBox="-5 -4.33 10 8.66" style={{ backgroundColor: "black" }}>
+        <Triangle style={{ fill: "white" }} />
+    </svg>;
+});
+"#;
+        File::create(routes.join("index.tsx"))
+            .context("creating qwik index route")?
+            .write_all(index_route.as_bytes())
+            .context("writing qwik index route")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs::read_to_string};
+
+    use serde_json::Value;
+
+    use crate::test_app_builder::{Framework, TestAppBuilder};
+
+    #[test]
+    fn pages_router_pages_import_different_components() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            pages_router_page_count: 5,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.pages().len(), 5);
+        let mut imports = app
+            .pages()
+            .iter()
+            .map(|page| {
+                assert!(page.starts_with(app.path().join("src/pages")));
+                let content = read_to_string(page).unwrap();
+                content
+                    .lines()
+                    .find(|line| line.starts_with("import Component from"))
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        imports.sort();
+        imports.dedup();
+        assert_eq!(imports.len(), 5);
+    }
+
+    #[test]
+    fn server_actions_are_submitted_by_forms() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            server_actions: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let actions = read_to_string(app.path().join("src/app/forms/actions.js")).unwrap();
+        assert!(actions.starts_with("\"use server\";\n"));
+        assert_eq!(actions.matches("export async function ").count(), 3);
+
+        let page = read_to_string(app.path().join("src/app/forms/page.jsx")).unwrap();
+        assert!(page.contains(
+            "import { submitTriangle0, submitTriangle1, submitTriangle2 } from \"./actions.js\";"
+        ));
+        assert!(page.contains("<form action={submitTriangle2}>"));
+    }
+
+    #[test]
+    fn nested_layouts_are_created_below_the_root_layout() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            nested_layouts: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let app_dir = app.path().join("src/app");
+        let layouts = app.nested_layouts();
+        assert_eq!(layouts.len(), 3);
+        let mut parent = app_dir.clone();
+        for layout in layouts {
+            assert_eq!(layout.file_name().unwrap(), "layout.jsx");
+            let dir = layout.parent().unwrap();
+            assert!(dir.starts_with(&parent) && dir != parent);
+            let content = read_to_string(layout).unwrap();
+            assert!(content.contains("{children}"));
+            assert!(content.contains("<Triangle"));
+            parent = dir.to_path_buf();
+        }
+        assert!(parent.join("page.jsx").exists());
+        assert!(app_dir.join("layout.jsx").exists());
+    }
+
+    #[test]
+    fn route_groups_contain_pages() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            route_groups: vec!["marketing".to_string(), "shop".to_string()],
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let app_dir = app.path().join("src/app");
+        assert_eq!(
+            app.route_groups(),
+            [app_dir.join("(marketing)"), app_dir.join("(shop)")]
+        );
+        for (i, group) in app.route_groups().iter().enumerate() {
+            assert!(group.is_dir());
+            let page = read_to_string(group.join(format!("group_{i}/page.jsx"))).unwrap();
+            assert!(page.contains("<Triangle"));
+        }
+        let layout = read_to_string(app_dir.join("(marketing)/layout.jsx")).unwrap();
+        assert!(layout.contains("{children}"));
+        assert!(!app_dir.join("(shop)/layout.jsx").exists());
+
+        assert!(TestAppBuilder {
+            module_count: 10,
+            route_groups: vec!["a/b".to_string()],
+            ..Default::default()
+        }
+        .build()
+        .is_err());
+    }
+
+    #[test]
+    fn node_builtins_are_only_imported_by_server_modules() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            node_builtin_imports: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let api_route = read_to_string(app.path().join("src/pages/api/builtins.js")).unwrap();
+        assert_eq!(api_route.matches(" from \"node:").count(), 10);
+        assert!(app
+            .modules()
+            .iter()
+            .all(|(module, _)| !read_to_string(module).unwrap().contains("node:")));
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(!bootstrap.contains("node:"));
+
+        let app = TestAppBuilder {
+            module_count: 50,
+            framework: Framework::Remix,
+            node_builtin_imports: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let index_route = read_to_string(app.path().join("app/routes/_index.jsx")).unwrap();
+        assert!(index_route.contains("import { readFile as readFile0 } from \"node:fs/promises\";"));
+        assert_eq!(index_route.matches(" from \"node:").count(), 3);
+        assert!(app
+            .modules()
+            .iter()
+            .all(|(module, _)| !read_to_string(module).unwrap().contains("node:")));
+    }
+
+    #[test]
+    fn qwik_components_use_component_dollar() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            framework: Framework::Qwik,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.modules().len(), 100);
+        for (module, _) in app.modules() {
+            assert_eq!(module.extension().unwrap(), "tsx");
+            assert!(read_to_string(module)
+                .unwrap()
+                .contains("export default component$("));
+        }
+        let index_route = read_to_string(app.path().join("src/routes/index.tsx")).unwrap();
+        assert!(index_route.contains("import Triangle from \"../triangle\";"));
+        assert!(!app.path().join("src/index.jsx").exists());
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["@builder.io/qwik"].is_string());
+        assert!(package_json["dependencies"].get("react").is_none());
+    }
+
+    #[test]
+    fn solid_components_import_solid_js() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            dynamic_import_count: 5,
+            framework: Framework::Solid,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.lazy_modules().len(), 5);
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        assert!(modules.iter().all(|module| !module.contains("React")));
+        assert!(modules
+            .iter()
+            .filter(|module| module.contains("lazy("))
+            .all(|module| module.contains("import { Suspense, lazy } from \"solid-js\";")));
+        assert!(modules
+            .iter()
+            .filter(|module| module.contains("function Triangle"))
+            .all(|module| module.contains("from \"solid-js\";")));
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import { render } from \"solid-js/web\";"));
+        let vite_config = read_to_string(app.path().join("vite.config.js")).unwrap();
+        assert!(vite_config.contains("import solid from \"vite-plugin-solid\";"));
+        assert!(vite_config.contains("plugins: [solid()]"));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["solid-js"].is_string());
+        assert!(package_json["devDependencies"]["vite-plugin-solid"].is_string());
+    }
+
+    #[test]
+    fn astro_islands_use_client_load() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            dynamic_import_count: 5,
+            framework: Framework::Astro,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.lazy_modules().len(), 5);
+        for island in app.lazy_modules() {
+            assert_eq!(island.extension().unwrap(), "jsx");
+            assert!(read_to_string(island).unwrap().contains("import React"));
+        }
+        for module in app.eager_modules() {
+            assert_eq!(module.extension().unwrap(), "astro");
+        }
+        let client_loads = app
+            .eager_modules()
+            .iter()
+            .map(|module| {
+                read_to_string(module)
+                    .unwrap()
+                    .matches(" client:load ")
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(client_loads, 5);
+
+        let index = read_to_string(app.path().join("src/pages/index.astro")).unwrap();
+        assert!(index.contains("import Triangle from \"../triangle.astro\";"));
+        let astro_config = read_to_string(app.path().join("astro.config.mjs")).unwrap();
+        assert!(astro_config.contains("integrations: [react()]"));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["astro"].is_string());
+        assert!(package_json["dependencies"]["@astrojs/react"].is_string());
+    }
+
+    #[test]
+    fn web_components_define_custom_elements() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            dynamic_import_count: 3,
+            framework: Framework::WebComponents,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let mut names = HashSet::new();
+        for (module, _) in app.modules() {
+            assert_eq!(module.extension().unwrap(), "js");
+            let content = read_to_string(module).unwrap();
+            let define = content
+                .lines()
+                .find(|line| line.starts_with("customElements.define(\""))
+                .unwrap_or_else(|| panic!("{} doesn't define an element", module.display()));
+            assert!(names.insert(define.to_string()));
+            assert!(!content.contains("react"));
+        }
+        assert!(app
+            .modules()
+            .iter()
+            .any(|(module, _)| read_to_string(module)
+                .unwrap()
+                .contains("        import(\"./")));
+
+        let bootstrap = read_to_string(app.path().join("src/index.js")).unwrap();
+        assert!(bootstrap.contains("import \"./triangle.js\";"));
+        assert!(bootstrap.contains("document.createElement(\"x-triangle\")"));
+        assert!(bootstrap.contains("document.body.appendChild(root);"));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package_json["dependencies"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn vue_typescript_components_use_typed_script_setup() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            dynamic_import_count: 3,
+            framework: Framework::Vue,
+            typescript: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        for (module, _) in app.modules() {
+            assert_eq!(module.extension().unwrap(), "vue");
+            let content = read_to_string(module).unwrap();
+            assert!(content.starts_with("<script setup lang=\"ts\">\n"));
+            assert!(content.contains("defineProps<{ style: Record<string, string> }>();"));
+            assert!(content.contains("\n<template>\n"));
+            assert!(!content.contains("react"));
+        }
+        assert!(app
+            .modules()
+            .iter()
+            .any(|(module, _)| read_to_string(module)
+                .unwrap()
+                .contains("defineAsyncComponent(() => import(\"./")));
+
+        let bootstrap = read_to_string(app.path().join("src/main.ts")).unwrap();
+        assert!(bootstrap.contains("import Triangle from \"./triangle.vue\";"));
+        let html = read_to_string(app.path().join("index.html")).unwrap();
+        assert!(html.contains("src=\"/src/main.ts\""));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["vue"].is_string());
+        assert!(package_json["devDependencies"]["@vitejs/plugin-vue"].is_string());
+        assert!(package_json["devDependencies"]["typescript"].is_string());
+    }
+
+    #[test]
+    fn vue_typescript_apps_have_no_dangling_imports() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            directories_count: 10,
+            dynamic_import_count: 5,
+            framework: Framework::Vue,
+            typescript: true,
+            descriptive_names: true,
+            css_file_count: 3,
+            package_json: Some(Default::default()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.verify().unwrap(), vec![]);
+        assert!(app
+            .modules()
+            .iter()
+            .all(|(module, _)| module.extension().unwrap() == "vue"));
+    }
+
+    #[test]
+    fn remix_routes_export_loader_and_component() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            framework: Framework::Remix,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let routes = std::fs::read_dir(app.path().join("app/routes"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(routes.len(), 4);
+        assert!(app.path().join("app/routes/_index.jsx").exists());
+        for route in routes {
+            let content = read_to_string(route).unwrap();
+            assert!(content.contains("export async function loader() {"));
+            assert!(content.contains("export default function Route() {"));
+        }
+        assert!(app.path().join("app/root.jsx").exists());
+        assert!(app.path().join("remix.config.js").exists());
+        assert!(app.path().join("src/detector.jsx").exists());
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["dependencies"]["@remix-run/react"].is_string());
+        assert!(package_json["devDependencies"]["@remix-run/dev"].is_string());
+    }
+}
@@ -0,0 +1,1524 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::prelude::*,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+
+use super::{
+    test_app::TestApp, Framework, PackageJsonConfig, TestAppBuilder, LOCALE_MESSAGE_COUNT,
+    SHARED_PACKAGE_NAME, THEME_VAR_USE_INTERVAL,
+};
+
+/// The number of sibling modules the computed specifiers of
+/// `dynamic_expression_count` select from.
+const DYNAMIC_EXPRESSION_CANDIDATES: usize = 3;
+
+/// The `devDependencies` needed for `tailwind`.
+const TAILWIND_DEV_DEPENDENCIES: [(&str, &str); 3] = [
+    ("autoprefixer", "^10.4.13"),
+    ("postcss", "^8.4.19"),
+    ("tailwindcss", "^3.2.4"),
+];
+
+/// The `devDependencies` needed for `scss`.
+const SCSS_DEV_DEPENDENCIES: [(&str, &str); 1] = [("sass", "^1.56.1")];
+
+/// The `devDependencies` needed for [Framework::Solid].
+const SOLID_DEV_DEPENDENCIES: [(&str, &str); 2] =
+    [("vite", "^3.2.4"), ("vite-plugin-solid", "^2.4.0")];
+
+/// The `devDependencies` needed for [Framework::WebComponents].
+const WEB_COMPONENTS_DEV_DEPENDENCIES: [(&str, &str); 1] = [("vite", "^3.2.4")];
+
+/// The `devDependencies` needed for [Framework::Vue].
+const VUE_DEV_DEPENDENCIES: [(&str, &str); 2] =
+    [("vite", "^3.2.4"), ("@vitejs/plugin-vue", "^3.2.0")];
+
+/// The `devDependencies` needed for `decorators`, `type_only_import_count` and
+/// `typescript`.
+const TYPESCRIPT_DEV_DEPENDENCIES: [(&str, &str); 1] = [("typescript", "^4.9.3")];
+
+/// A 1x1 transparent PNG, used for the `.png` files of `public_file_count`.
+const TRANSPARENT_PIXEL_PNG: [u8; 67] = [
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+    0x89, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+/// The `devDependencies` needed for [Framework::Remix].
+const REMIX_DEV_DEPENDENCIES: [(&str, &str); 1] = [("@remix-run/dev", "^1.8.2")];
+
+/// The `devDependencies` needed for `unit_tests`.
+const UNIT_TEST_DEV_DEPENDENCIES: [(&str, &str); 3] = [
+    ("@testing-library/react", "^13.4.0"),
+    ("jest", "^29.3.1"),
+    ("jest-environment-jsdom", "^29.3.1"),
+];
+
+/// The aliases that make the React templates use Preact. More specific
+/// aliases come first, as bundlers also match aliases as prefixes.
+const PREACT_ALIASES: [(&str, &str); 4] = [
+    ("react/jsx-runtime", "preact/jsx-runtime"),
+    ("react-dom/test-utils", "preact/test-utils"),
+    ("react-dom", "preact/compat"),
+    ("react", "preact/compat"),
+];
+
+impl TestAppBuilder {
+    /// Writes the `src/styles/_mixins.scss` shared by the `.scss` files of
+    /// `scss`.
+    pub(super) fn write_scss_mixins(&self, src: &Path) -> Result<()> {
+        create_dir_all(src.join("styles")).context("creating styles dir")?;
+        let mixins = r#"@mixin interactive {
+    cursor: pointer;
+    transition: opacity 0.2s;
+
+    &:hover {
+        opacity: 0.75;
+    }
+}
+
+@mixin tinted($color) {
+    fill: $color;
+    stroke: rgba($color, 0.5);
+}
+"#;
+        File::create(src.join("styles/_mixins.scss"))
+            .context("creating scss mixins")?
+            .write_all(mixins.as_bytes())
+            .context("writing scss mixins")?;
+        Ok(())
+    }
+
+    /// Writes the chain of `css_import_depth` CSS files in `src/styles`.
+    pub(super) fn write_css_import_chain(&self, app: &mut TestApp, src: &Path) -> Result<()> {
+        let styles = src.join("styles");
+        create_dir_all(&styles).context("creating styles dir")?;
+        for i in 0..self.css_import_depth {
+            let css = if i + 1 < self.css_import_depth {
+                format!(
+                    r#"@import "./import_{}.css";
+
+.triangle-{i} {{
+    stroke-width: {i}px;
+}}
+"#,
+                    i + 1
+                )
+            } else {
+                r#"svg {
+    display: block;
+}
+
+polygon {
+    stroke: black;
+    stroke-width: 0.1px;
+}
+"#
+                .to_string()
+            };
+            let file = styles.join(format!("import_{i}.css"));
+            File::create(&file)
+                .context("creating css file")?
+                .write_all(css.as_bytes())
+                .context("writing css file")?;
+            app.css_import_chain.push(file);
+        }
+        Ok(())
+    }
+
+    /// Writes the custom properties of `css_theme_vars` to
+    /// `src/styles/theme.css` and the rules referencing them to
+    /// `src/styles/components.css`.
+    pub(super) fn write_css_theme(&self, src: &Path) -> Result<()> {
+        let styles = src.join("styles");
+        create_dir_all(&styles).context("creating styles dir")?;
+        let mut theme = ":root {\n".to_string();
+        let mut components = String::new();
+        for i in 0..self.css_theme_vars {
+            theme.push_str(&format!(
+                "    --theme-color-{i}: hsl({}, 70%, 50%);\n",
+                i * 37 % 360
+            ));
+            if i % THEME_VAR_USE_INTERVAL == 0 {
+                components.push_str(&format!(
+                    ".theme-{i} polygon {{\n    fill: var(--theme-color-{i});\n}}\n\n"
+                ));
+            }
+        }
+        theme.push_str("}\n");
+        File::create(styles.join("theme.css"))
+            .context("creating theme.css")?
+            .write_all(theme.as_bytes())
+            .context("writing theme.css")?;
+        File::create(styles.join("components.css"))
+            .context("creating components.css")?
+            .write_all(components.as_bytes())
+            .context("writing components.css")?;
+        Ok(())
+    }
+
+    /// Writes the `@tailwind` directives to `src/globals.css`, and the
+    /// Tailwind and PostCSS configs.
+    pub(super) fn write_tailwind_config(&self, path: &Path, src: &Path) -> Result<()> {
+        let globals = r#"@tailwind base;
+@tailwind components;
+@tailwind utilities;
+"#;
+        File::create(src.join("globals.css"))
+            .context("creating globals.css")?
+            .write_all(globals.as_bytes())
+            .context("writing globals.css")?;
+
+        let tailwind_config = r#"module.exports = {
+    content: ["./src/**/*.{js,jsx,ts,tsx}"],
+    theme: {
+        extend: {},
+    },
+    plugins: [],
+};
+"#;
+        File::create(path.join("tailwind.config.js"))
+            .context("creating tailwind config")?
+            .write_all(tailwind_config.as_bytes())
+            .context("writing tailwind config")?;
+
+        let postcss_config = r#"module.exports = {
+    plugins: {
+        tailwindcss: {},
+        autoprefixer: {},
+    },
+};
+"#;
+        File::create(path.join("postcss.config.js"))
+            .context("creating postcss config")?
+            .write_all(postcss_config.as_bytes())
+            .context("writing postcss config")?;
+        Ok(())
+    }
+
+    /// Writes the static files of `public_file_count` to `public/assets`.
+    pub(super) fn write_public_files(&self, app: &mut TestApp, path: &Path) -> Result<()> {
+        let assets = path.join("public/assets");
+        create_dir_all(&assets).context("creating public assets dir")?;
+        for i in 0..self.public_file_count {
+            let (file, content) = match i % 4 {
+                0 => (
+                    assets.join(format!("note_{i}.txt")),
+                    format!("Static file {i}\n").into_bytes(),
+                ),
+                1 => (
+                    assets.join(format!("data_{i}.json")),
+                    format!("{:#}", json!({ "index": i, "name": format!("Triangle {i}") }))
+                        .into_bytes(),
+                ),
+                2 => (
+                    assets.join(format!("triangle_{i}.svg")),
+                    format!(
+                        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-5 -4.33 10 8.66"><title>Triangle {i}</title><polygon points="-5,4.33 0,-4.33 5,4.33" /></svg>
+"#
+                    )
+                    .into_bytes(),
+                ),
+                _ => (
+                    assets.join(format!("pixel_{i}.png")),
+                    TRANSPARENT_PIXEL_PNG.to_vec(),
+                ),
+            };
+            File::create(&file)
+                .context("creating public file")?
+                .write_all(&content)
+                .context("writing public file")?;
+            app.public_files.push(file);
+        }
+        Ok(())
+    }
+
+    /// Writes the Next.js `middleware.js` and the `src/middleware_util.js` it
+    /// imports.
+    pub(super) fn write_middleware(&self, path: &Path, src: &Path) -> Result<()> {
+        // The middleware is e. g. used by Next.js and runs in the edge runtime
+        let middleware_util = r#"export function isPagePath(pathname) {
+    return !pathname.startsWith("/_next/") && !pathname.startsWith("/api/");
+}
+"#;
+        File::create(src.join("middleware_util.js"))
+            .context("creating middleware util")?
+            .write_all(middleware_util.as_bytes())
+            .context("writing middleware util")?;
+
+        let middleware = r#"import { NextResponse } from "next/server";
+import { isPagePath } from "./src/middleware_util.js";
+
+export function middleware(request) {
+    if (isPagePath(request.nextUrl.pathname)) {
+        return NextResponse.next();
+    }
+}
+
+export const config = {
+    matcher: ["/((?!_next/static|favicon.ico).*)"],
+};
+"#;
+        File::create(path.join("middleware.js"))
+            .context("creating middleware")?
+            .write_all(middleware.as_bytes())
+            .context("writing middleware")?;
+        Ok(())
+    }
+
+    /// Writes the `.gitignore` and the `README.md` of `scaffold_repo`.
+    pub(super) fn write_repo_scaffold(&self, path: &Path) -> Result<()> {
+        let gitignore = r#"node_modules
+dist
+.next
+.turbo
+"#;
+        File::create(path.join(".gitignore"))
+            .context("creating .gitignore")?
+            .write_all(gitignore.as_bytes())
+            .context("writing .gitignore")?;
+
+        let max_depth = self.max_depth.map_or_else(
+            || "unlimited".to_string(),
+            |max_depth| max_depth.to_string(),
+        );
+        let flatness = if self.flatness_profile.is_empty() {
+            self.flatness.to_string()
+        } else {
+            let profile = self
+                .flatness_profile
+                .iter()
+                .map(|flatness| flatness.to_string())
+                .collect::<Vec<_>>();
+            format!("{} (per depth)", profile.join(", "))
+        };
+        let readme = format!(
+            r#"# Turbopack Test App
+
+This app was generated by `turbopack-create-test-app` with the following
+parameters:
+
+- Framework: {:?}
+- Module count: {}
+- Directories count: {}
+- Dynamic import count: {}
+- Flatness: {flatness}
+- Max depth: {max_depth}
+"#,
+            self.framework, self.module_count, self.directories_count, self.dynamic_import_count,
+        );
+        File::create(path.join("README.md"))
+            .context("creating README.md")?
+            .write_all(readme.as_bytes())
+            .context("writing README.md")?;
+        Ok(())
+    }
+
+    /// Writes the configs aliasing React to Preact for the different
+    /// bundlers.
+    pub(super) fn write_preact_configs(&self, path: &Path) -> Result<()> {
+        let aliases = PREACT_ALIASES
+            .iter()
+            .map(|(from, to)| format!("            \"{from}\": \"{to}\",\n"))
+            .collect::<String>();
+
+        // This config is e. g. used by Vite
+        let vite_config = format!(
+            r#"export default {{
+    resolve: {{
+        alias: {{
+{aliases}        }},
+    }},
+}};
+"#
+        );
+        File::create(path.join("vite.config.js"))
+            .context("creating vite config")?
+            .write_all(vite_config.as_bytes())
+            .context("writing vite config")?;
+
+        // This config is e. g. used by webpack
+        let webpack_config = format!(
+            r#"module.exports = {{
+    resolve: {{
+        alias: {{
+{aliases}        }},
+    }},
+}};
+"#
+        );
+        File::create(path.join("webpack.config.js"))
+            .context("creating webpack config")?
+            .write_all(webpack_config.as_bytes())
+            .context("writing webpack config")?;
+
+        // The paths are e. g. used by TypeScript and Next.js
+        let paths = PREACT_ALIASES
+            .iter()
+            .map(|(from, to)| (from.to_string(), json!([format!("./node_modules/{to}")])))
+            .collect::<Map<_, _>>();
+        let jsconfig = json!({
+            "compilerOptions": {
+                "baseUrl": ".",
+                "paths": paths,
+            }
+        });
+        File::create(path.join("jsconfig.json"))
+            .context("creating jsconfig.json")?
+            .write_all(format!("{:#}", jsconfig).as_bytes())
+            .context("writing jsconfig.json")?;
+        Ok(())
+    }
+
+    /// Writes the `Dockerfile` and the `.dockerignore` of `dockerfile`.
+    pub(super) fn write_dockerfile(&self, path: &Path) -> Result<()> {
+        let start_command = self
+            .framework
+            .start_command()
+            .split(' ')
+            .map(|arg| format!("\"{arg}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let dockerfile = format!(
+            r#"FROM node:18-alpine AS deps
+WORKDIR /app
+COPY package.json ./
+RUN npm install
+
+FROM node:18-alpine AS build
+WORKDIR /app
+COPY --from=deps /app/node_modules ./node_modules
+COPY . .
+# Runs `{}`, unless the package.json defines a different build script.
+RUN npm run build
+
+FROM node:18-alpine AS runtime
+WORKDIR /app
+ENV NODE_ENV=production
+COPY --from=build /app ./
+CMD ["npx", {start_command}]
+"#,
+            self.framework.build_command()
+        );
+        File::create(path.join("Dockerfile"))
+            .context("creating Dockerfile")?
+            .write_all(dockerfile.as_bytes())
+            .context("writing Dockerfile")?;
+
+        let dockerignore = r#"node_modules
+dist
+.next
+.turbo
+Dockerfile
+.dockerignore
+"#;
+        File::create(path.join(".dockerignore"))
+            .context("creating .dockerignore")?
+            .write_all(dockerignore.as_bytes())
+            .context("writing .dockerignore")?;
+        Ok(())
+    }
+
+    /// Writes the `package.json`, including the `devDependencies` implied by
+    /// the options.
+    pub(super) fn write_package_json(
+        &self,
+        path: &Path,
+        package_json_config: &PackageJsonConfig,
+    ) -> Result<()> {
+        // These dependencies are needed
+        let mut dependencies = match self.framework {
+            Framework::React => json!({
+                "react": package_json_config.react_version.clone(),
+                "react-dom": package_json_config.react_version.clone(),
+            }),
+            Framework::Preact => json!({
+                "preact": package_json_config.preact_version.clone(),
+            }),
+            Framework::Qwik => json!({
+                "@builder.io/qwik": package_json_config.qwik_version.clone(),
+                "@builder.io/qwik-city": package_json_config.qwik_version.clone(),
+            }),
+            Framework::Solid => json!({
+                "solid-js": package_json_config.solid_version.clone(),
+            }),
+            Framework::Astro => json!({
+                "astro": package_json_config.astro_version.clone(),
+                "@astrojs/react": package_json_config.astro_react_version.clone(),
+                "react": package_json_config.react_version.clone(),
+                "react-dom": package_json_config.react_version.clone(),
+            }),
+            Framework::Remix => json!({
+                "@remix-run/node": package_json_config.remix_version.clone(),
+                "@remix-run/react": package_json_config.remix_version.clone(),
+                "@remix-run/serve": package_json_config.remix_version.clone(),
+                "react": package_json_config.react_version.clone(),
+                "react-dom": package_json_config.react_version.clone(),
+            }),
+            Framework::WebComponents => json!({}),
+            Framework::Vue => json!({
+                "vue": package_json_config.vue_version.clone(),
+            }),
+        };
+        if self.styled_components {
+            dependencies["styled-components"] =
+                json!(package_json_config.styled_components_version.clone());
+        }
+        for (name, version) in &package_json_config.extra_dependencies {
+            dependencies[name] = json!(version);
+        }
+        let mut package_json = json!({
+            "name": "turbopack-test-app",
+            "private": true,
+            "version": "0.0.0",
+            "dependencies": dependencies,
+        });
+        if self.subpath_exports && self.framework.uses_react_templates() {
+            package_json["workspaces"] = json!(["packages/*"]);
+            package_json["dependencies"][SHARED_PACKAGE_NAME] = json!("*");
+        }
+        let mut scripts = package_json_config.scripts.clone();
+        if self.dockerfile {
+            scripts
+                .entry("build".to_string())
+                .or_insert_with(|| self.framework.build_command().to_string());
+        }
+        if !scripts.is_empty() {
+            package_json["scripts"] = json!(scripts);
+        }
+        let mut dev_dependencies = package_json_config.dev_dependencies.clone();
+        let implied_dev_dependencies = [
+            (self.tailwind, &TAILWIND_DEV_DEPENDENCIES[..]),
+            (self.unit_tests, &UNIT_TEST_DEV_DEPENDENCIES[..]),
+            (self.decorators > 0, &TYPESCRIPT_DEV_DEPENDENCIES[..]),
+            (self.scss, &SCSS_DEV_DEPENDENCIES[..]),
+            (
+                self.type_only_import_count > 0,
+                &TYPESCRIPT_DEV_DEPENDENCIES[..],
+            ),
+            (
+                self.framework == Framework::Solid,
+                &SOLID_DEV_DEPENDENCIES[..],
+            ),
+            (
+                self.framework == Framework::Remix,
+                &REMIX_DEV_DEPENDENCIES[..],
+            ),
+            (
+                self.framework == Framework::WebComponents,
+                &WEB_COMPONENTS_DEV_DEPENDENCIES[..],
+            ),
+            (self.framework == Framework::Vue, &VUE_DEV_DEPENDENCIES[..]),
+            (
+                (self.framework == Framework::Vue || self.metadata_routes) && self.typescript,
+                &TYPESCRIPT_DEV_DEPENDENCIES[..],
+            ),
+        ];
+        for (enabled, implied) in implied_dev_dependencies {
+            if !enabled {
+                continue;
+            }
+            for (name, version) in implied {
+                dev_dependencies
+                    .entry(name.to_string())
+                    .or_insert_with(|| version.to_string());
+            }
+        }
+        if !dev_dependencies.is_empty() {
+            package_json["devDependencies"] = json!(dev_dependencies);
+        }
+        File::create(path.join("package.json"))
+            .context("creating package.json")?
+            .write_all(format!("{:#}", package_json).as_bytes())
+            .context("writing package.json")?;
+        Ok(())
+    }
+
+    /// Writes a message catalog to `src/locales` for each of the `locales`.
+    pub(super) fn write_locales(&self, app: &mut TestApp, src: &Path) -> Result<()> {
+        let locales_dir = src.join("locales");
+        create_dir_all(&locales_dir).context("creating locales dir")?;
+        for locale in &self.locales {
+            let messages = (0..LOCALE_MESSAGE_COUNT)
+                .map(|i| {
+                    (
+                        format!("message_{i}"),
+                        Value::String(format!("Message {i} ({locale})")),
+                    )
+                })
+                .collect::<Map<_, _>>();
+            let file = locales_dir.join(format!("{locale}.json"));
+            File::create(&file)
+                .context("creating locale file")?
+                .write_all(format!("{:#}", Value::Object(messages)).as_bytes())
+                .context("writing locale file")?;
+            app.locales.push(file);
+        }
+        Ok(())
+    }
+
+    /// Writes the `next.config.js` requiring the variables of
+    /// `next_public_env_count`, and returns their names.
+    pub(super) fn write_next_config(&self, path: &Path) -> Result<Vec<String>> {
+        let required = (0..self.next_public_env_count)
+            .map(|i| format!("NEXT_PUBLIC_TRIANGLE_{i}"))
+            .collect::<Vec<_>>();
+        let names = required
+            .iter()
+            .map(|name| format!("\"{name}\""))
+            .collect::<Vec<_>>();
+        let env = required
+            .iter()
+            .map(|name| format!("        {name}: process.env.{name},\n"))
+            .collect::<String>();
+        let next_config = format!(
+            r#"const required = [{}];
+
+const missing = required.filter((name) => !process.env[name]);
+if (missing.length > 0) {{
+    throw new Error(`Missing required public env variables: ${{missing.join(", ")}}`);
+}}
+
+/** @type {{import("next").NextConfig}} */
+module.exports = {{
+    env: {{
+{env}    }},
+}};
+"#,
+            names.join(", ")
+        );
+        File::create(path.join("next.config.js"))
+            .context("creating next.config.js")?
+            .write_all(next_config.as_bytes())
+            .context("writing next.config.js")?;
+        Ok(required)
+    }
+
+    /// Writes the `packages/shared` workspace package of `subpath_exports`.
+    pub(super) fn write_shared_package(&self, app: &TestApp) -> Result<()> {
+        let shared = app.path().join("packages/shared");
+        create_dir_all(shared.join("src/internal")).context("creating shared package dir")?;
+
+        let package_json = json!({
+            "name": SHARED_PACKAGE_NAME,
+            "private": true,
+            "version": "0.0.0",
+            "type": "module",
+            "exports": {
+                ".": "./src/index.js",
+                "./colors": "./src/colors.js",
+                "./sizes": {
+                    "browser": "./src/sizes.browser.js",
+                    "default": "./src/sizes.js",
+                },
+                "./package.json": "./package.json",
+            },
+            "imports": {
+                "#internal/*": "./src/internal/*.js",
+            },
+        });
+        let files = [
+            ("package.json", format!("{:#}", package_json)),
+            (
+                "src/index.js",
+                r#"export * from "./colors.js";
+export * from "./sizes.js";
+"#
+                .to_string(),
+            ),
+            (
+                "src/colors.js",
+                r##"import { palette } from "#internal/palette";
+
+export const fill = palette[0];
+"##
+                .to_string(),
+            ),
+            ("src/sizes.js", "export const size = 10;\n".to_string()),
+            (
+                "src/sizes.browser.js",
+                "export const size = window.innerWidth / 100;\n".to_string(),
+            ),
+            (
+                "src/internal/palette.js",
+                r#"export const palette = ["white", "silver", "gray"];
+"#
+                .to_string(),
+            ),
+        ];
+        for (name, content) in files {
+            File::create(shared.join(name))
+                .context("creating shared package file")?
+                .write_all(content.as_bytes())
+                .context("writing shared package file")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the modules only declaring types and the index importing them
+    /// with `import type`.
+    pub(super) fn write_type_only_imports(&self, app: &mut TestApp) -> Result<()> {
+        let types_dir = app.path().join("src/types");
+        create_dir_all(&types_dir).context("creating types dir")?;
+
+        let mut index = String::new();
+        let mut shapes = String::new();
+        for i in 0..self.type_only_import_count {
+            let content = format!(
+                r#"export interface Shape{i} {{
+    side: number;
+    fill: Fill{i};
+}}
+
+export type Fill{i} = "white" | "black";
+"#
+            );
+            let file = types_dir.join(format!("shape_{i}.ts"));
+            File::create(&file)
+                .context("creating type declarations")?
+                .write_all(content.as_bytes())
+                .context("writing type declarations")?;
+            app.type_declarations.push(file);
+            index.push_str(&format!(
+                "import type {{ Shape{i} }} from \"./shape_{i}\";\n"
+            ));
+            shapes.push_str(&format!(
+                "const shape{i}: Shape{i} = {{ side: {}, fill: \"white\" }};\n",
+                i + 1
+            ));
+        }
+        let sides = (0..self.type_only_import_count)
+            .map(|i| format!("shape{i}.side"))
+            .collect::<Vec<_>>();
+        index.push_str(&format!(
+            "\n{shapes}\nexport const sides = [{}];\n",
+            sides.join(", ")
+        ));
+        File::create(types_dir.join("index.ts"))
+            .context("creating types index")?
+            .write_all(index.as_bytes())
+            .context("writing types index")?;
+        Ok(())
+    }
+
+    /// Writes the candidate modules of `dynamic_expression_count` and the index
+    /// importing them with computed specifiers.
+    pub(super) fn write_dynamic_expressions(&self, app: &mut TestApp) -> Result<()> {
+        let dynamic_dir = app.path().join("src/dynamic");
+        create_dir_all(&dynamic_dir).context("creating dynamic dir")?;
+
+        for i in 0..DYNAMIC_EXPRESSION_CANDIDATES {
+            let content = format!(
+                r#"import React from "react";
+
+export default function Variant{i}({{ style }}) {{
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{style}} />;
+}}
+"#
+            );
+            let file = dynamic_dir.join(format!("variant_{i}.jsx"));
+            File::create(&file)
+                .context("creating dynamic variant")?
+                .write_all(content.as_bytes())
+                .context("writing dynamic variant")?;
+            app.dynamic_variants.push(file);
+        }
+
+        let mut index = String::new();
+        for i in 0..self.dynamic_expression_count {
+            index.push_str(&format!(
+                r#"export function loadVariant{i}(variant) {{
+    return import(`./variant_${{variant}}.jsx`);
+}}
+
+"#
+            ));
+        }
+        let loads = (0..self.dynamic_expression_count)
+            .map(|i| {
+                format!(
+                    "    loadVariant{i}((Date.now() + {i}) % {DYNAMIC_EXPRESSION_CANDIDATES}),\n"
+                )
+            })
+            .collect::<String>();
+        index.push_str(&format!(
+            "export const variants = Promise.all([\n{loads}]);\n"
+        ));
+        File::create(dynamic_dir.join("index.js"))
+            .context("creating dynamic index")?
+            .write_all(index.as_bytes())
+            .context("writing dynamic index")?;
+        Ok(())
+    }
+
+    /// Writes the shared workers and audio worklets together with the index
+    /// registering them.
+    pub(super) fn write_workers(&self, app: &mut TestApp) -> Result<()> {
+        let workers_dir = app.path().join("src/workers");
+        create_dir_all(&workers_dir).context("creating workers dir")?;
+
+        let mut index = String::new();
+        if self.shared_worker_count > 0 {
+            index.push_str("export const sharedWorkers = [\n");
+            for i in 0..self.shared_worker_count {
+                let content = format!(
+                    r#"let connections = 0;
+
+self.onconnect = (event) => {{
+    const port = event.ports[0];
+    connections += 1;
+    port.onmessage = ({{ data }}) => port.postMessage({{ worker: {i}, connections, data }});
+}};
+"#
+                );
+                let file = workers_dir.join(format!("shared_worker_{i}.js"));
+                File::create(&file)
+                    .context("creating shared worker")?
+                    .write_all(content.as_bytes())
+                    .context("writing shared worker")?;
+                app.shared_workers.push(file);
+                index.push_str(&format!(
+                    "    new SharedWorker(new URL(\"./shared_worker_{i}.js\", import.meta.url), \
+                     {{ type: \"module\" }}),\n"
+                ));
+            }
+            index.push_str("];\n");
+        }
+        if self.audio_worklet_count > 0 {
+            if !index.is_empty() {
+                index.push('\n');
+            }
+            index.push_str("const audioContext = new AudioContext();\n\n");
+            index.push_str("export const audioWorklets = Promise.all([\n");
+            for i in 0..self.audio_worklet_count {
+                let content = format!(
+                    r#"class GainProcessor{i} extends AudioWorkletProcessor {{
+    process(inputs, outputs) {{
+        const [input] = inputs;
+        const [output] = outputs;
+        input.forEach((channel, c) => {{
+            channel.forEach((sample, s) => {{
+                output[c][s] = sample * 0.5;
+            }});
+        }});
+        return true;
+    }}
+}}
+
+registerProcessor("gain-processor-{i}", GainProcessor{i});
+"#
+                );
+                let file = workers_dir.join(format!("audio_worklet_{i}.js"));
+                File::create(&file)
+                    .context("creating audio worklet")?
+                    .write_all(content.as_bytes())
+                    .context("writing audio worklet")?;
+                app.audio_worklets.push(file);
+                index.push_str(&format!(
+                    "    audioContext.audioWorklet.addModule(new URL(\"./audio_worklet_{i}.js\", \
+                     import.meta.url)),\n"
+                ));
+            }
+            index.push_str("]);\n");
+        }
+        File::create(workers_dir.join("index.js"))
+            .context("creating workers index")?
+            .write_all(index.as_bytes())
+            .context("writing workers index")?;
+        Ok(())
+    }
+
+    /// Writes the classes using legacy decorators, the shared decorators, an
+    /// index instantiating every class and a `tsconfig.json` enabling
+    /// `experimentalDecorators`.
+    pub(super) fn write_decorated_modules(&self, app: &mut TestApp) -> Result<()> {
+        let path = app.path().to_path_buf();
+        let decorators_dir = path.join("src/decorators");
+        create_dir_all(&decorators_dir).context("creating decorators dir")?;
+
+        let decorators = r#"export function sealed(constructor: Function) {
+    Object.seal(constructor);
+    Object.seal(constructor.prototype);
+}
+
+export function logged(target: any, propertyKey: string, descriptor: PropertyDescriptor) {
+    const original = descriptor.value;
+    descriptor.value = function (...args: any[]) {
+        console.debug(`${target.constructor.name}.${propertyKey}`);
+        return original.apply(this, args);
+    };
+}
+"#;
+        File::create(decorators_dir.join("decorators.ts"))
+            .context("creating decorators")?
+            .write_all(decorators.as_bytes())
+            .context("writing decorators")?;
+
+        let mut index = String::new();
+        let mut instances = Vec::with_capacity(self.decorators);
+        for i in 0..self.decorators {
+            let content = format!(
+                r#"import {{ logged, sealed }} from "./decorators";
+
+@sealed
+export class Triangle{i} {{
+    constructor(private readonly side: number) {{}}
+
+    @logged
+    area(): number {{
+        return (Math.sqrt(3) / 4) * this.side * this.side;
+    }}
+}}
+"#
+            );
+            let file = decorators_dir.join(format!("triangle_{i}.ts"));
+            File::create(&file)
+                .context("creating decorated module")?
+                .write_all(content.as_bytes())
+                .context("writing decorated module")?;
+            app.decorated_modules.push(file);
+            index.push_str(&format!(
+                "import {{ Triangle{i} }} from \"./triangle_{i}\";\n"
+            ));
+            instances.push(format!("new Triangle{i}({}).area()", i + 1));
+        }
+        index.push_str(&format!(
+            "\nexport const areas = [{}];\n",
+            instances.join(", ")
+        ));
+        File::create(decorators_dir.join("index.ts"))
+            .context("creating decorators index")?
+            .write_all(index.as_bytes())
+            .context("writing decorators index")?;
+
+        let tsconfig = json!({
+            "compilerOptions": {
+                "target": "es2019",
+                "module": "esnext",
+                "moduleResolution": "node",
+                "jsx": "preserve",
+                "allowJs": true,
+                "experimentalDecorators": true,
+            },
+            "include": ["src"],
+        });
+        File::create(path.join("tsconfig.json"))
+            .context("creating tsconfig.json")?
+            .write_all(format!("{:#}", tsconfig).as_bytes())
+            .context("writing tsconfig.json")?;
+
+        Ok(())
+    }
+
+    /// Writes the app router metadata routes of `metadata_routes` into
+    /// `app_dir`. The sitemap lists the pages of the app router.
+    pub(super) fn write_metadata_routes(&self, app: &mut TestApp, app_dir: &Path) -> Result<()> {
+        let (extension, jsx_extension) = if self.typescript {
+            ("ts", "tsx")
+        } else {
+            ("js", "jsx")
+        };
+        let (import_type, sitemap_type, robots_type) = if self.typescript {
+            (
+                "import type { MetadataRoute } from \"next\";\n\n",
+                ": MetadataRoute.Sitemap",
+                ": MetadataRoute.Robots",
+            )
+        } else {
+            ("", "", "")
+        };
+
+        let mut urls = vec!["/".to_string(), "/app".to_string(), "/client".to_string()];
+        urls.extend((0..self.route_groups.len()).map(|i| format!("/group_{i}")));
+        let entries = urls
+            .iter()
+            .map(|url| {
+                format!(
+                    "        {{ url: \"https://example.com{url}\", changeFrequency: \"daily\" \
+                     }},\n"
+                )
+            })
+            .collect::<String>();
+        let sitemap = format!(
+            r#"{import_type}export default function sitemap(){sitemap_type} {{
+    return [
+{entries}    ];
+}}
+"#
+        );
+        let robots = format!(
+            r#"{import_type}export default function robots(){robots_type} {{
+    return {{
+        rules: {{ userAgent: "*", allow: "/", disallow: "/api/" }},
+        sitemap: "https://example.com/sitemap.xml",
+    }};
+}}
+"#
+        );
+        let opengraph_image = r#"import React from "react";
+import { ImageResponse } from "next/server";
+
+export const size = { width: 1200, height: 630 };
+export const contentType = "image/png";
+
+export default function OpengraphImage() {
+    return new ImageResponse(
+        <div style={{ display: "flex", width: "100%", height: "100%", alignItems: "center", justifyContent: "center", backgroundColor: "black", color: "white" }}>
+            Turbopack Test App
+        </div>,
+        size
+    );
+}
+"#;
+        for (name, extension, content) in [
+            ("sitemap", extension, sitemap.as_str()),
+            ("robots", extension, robots.as_str()),
+            ("opengraph-image", jsx_extension, opengraph_image),
+        ] {
+            let file = app_dir.join(format!("{name}.{extension}"));
+            File::create(&file)
+                .context("creating metadata route")?
+                .write_all(content.as_bytes())
+                .context("writing metadata route")?;
+            app.metadata_routes.push(file);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs::read_to_string};
+
+    use serde_json::Value;
+
+    use crate::test_app_builder::{Framework, PackageJsonConfig, TestAppBuilder};
+
+    #[test]
+    fn scaffold_repo_writes_gitignore_and_readme() {
+        let app = TestAppBuilder {
+            module_count: 42,
+            scaffold_repo: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let gitignore = read_to_string(app.path().join(".gitignore")).unwrap();
+        assert!(gitignore.lines().any(|line| line == "node_modules"));
+        let readme = read_to_string(app.path().join("README.md")).unwrap();
+        assert!(readme.contains("Module count: 42"));
+    }
+
+    #[test]
+    fn package_json_contains_scripts_and_dev_dependencies() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            package_json: Some(PackageJsonConfig {
+                scripts: [("dev".to_string(), "vite".to_string())]
+                    .into_iter()
+                    .collect(),
+                dev_dependencies: [("vite".to_string(), "^3.2.0".to_string())]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package_json["scripts"]["dev"], "vite");
+        assert_eq!(package_json["devDependencies"]["vite"], "^3.2.0");
+        assert_eq!(package_json["dependencies"]["react"], "^18.2.0");
+    }
+
+    #[test]
+    fn next_config_requires_public_env_from_dotenv() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            import_meta_env_count: 1,
+            next_public_env_count: 2,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let next_config = read_to_string(app.path().join("next.config.js")).unwrap();
+        assert!(next_config.contains("process.env[name]"));
+        assert!(next_config.contains("throw new Error("));
+        let dotenv = read_to_string(app.path().join(".env")).unwrap();
+        assert!(dotenv.starts_with("VITE_TRIANGLE_0="));
+        for i in 0..2 {
+            let name = format!("NEXT_PUBLIC_TRIANGLE_{i}");
+            assert!(next_config.contains(&format!("\"{name}\"")));
+            assert!(next_config.contains(&format!("process.env.{name}")));
+            assert!(dotenv
+                .lines()
+                .any(|line| line.starts_with(&format!("{name}="))));
+        }
+    }
+
+    #[test]
+    fn middleware_is_generated_in_app_root() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            middleware: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let middleware = read_to_string(app.path().join("middleware.js")).unwrap();
+        assert!(middleware.contains("export function middleware("));
+        assert!(middleware.contains("export const config = {"));
+        assert!(middleware.contains("NextResponse.next()"));
+        assert!(app.path().join("src/middleware_util.js").exists());
+    }
+
+    #[test]
+    fn dockerfile_builds_with_framework_command() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            framework: Framework::Solid,
+            dockerfile: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let dockerfile = read_to_string(app.path().join("Dockerfile")).unwrap();
+        assert_eq!(
+            dockerfile
+                .lines()
+                .filter(|line| line.starts_with("FROM "))
+                .count(),
+            3
+        );
+        assert!(dockerfile.contains("RUN npm run build\n"));
+        assert!(dockerfile.contains("CMD [\"npx\", \"vite\", \"preview\", \"--host\"]"));
+        let dockerignore = read_to_string(app.path().join(".dockerignore")).unwrap();
+        assert!(dockerignore.lines().any(|line| line == "node_modules"));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package_json["scripts"]["build"], "vite build");
+    }
+
+    #[test]
+    fn decorated_classes_are_generated() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            decorators: 3,
+            typescript: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.decorated_modules().len(), 3);
+        for module in app.decorated_modules() {
+            let content = read_to_string(module).unwrap();
+            assert!(content.contains("@sealed\nexport class Triangle"));
+            assert!(content.contains("    @logged\n    area(): number {"));
+        }
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./decorators/index.ts\";"));
+
+        let tsconfig: Value =
+            serde_json::from_str(&read_to_string(app.path().join("tsconfig.json")).unwrap())
+                .unwrap();
+        assert_eq!(tsconfig["compilerOptions"]["experimentalDecorators"], true);
+    }
+
+    #[test]
+    fn decorators_require_typescript() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = TestAppBuilder {
+            target: Some(dir.path().to_path_buf()),
+            module_count: 10,
+            decorators: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "decorators require typescript");
+        assert!(!dir.path().join("src").exists());
+    }
+
+    #[test]
+    fn type_only_imports_require_typescript() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = TestAppBuilder {
+            target: Some(dir.path().to_path_buf()),
+            module_count: 10,
+            type_only_import_count: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "type-only imports require typescript");
+        assert!(!dir.path().join("src").exists());
+    }
+
+    #[test]
+    fn type_only_imports_reference_type_declarations() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            type_only_import_count: 3,
+            typescript: true,
+            package_json: Some(Default::default()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.type_declarations().len(), 3);
+        let index = read_to_string(app.path().join("src/types/index.ts")).unwrap();
+        let imports = index
+            .lines()
+            .filter(|line| line.starts_with("import"))
+            .collect::<Vec<_>>();
+        assert_eq!(imports.len(), 3);
+        for (import, declarations) in imports.iter().zip(app.type_declarations()) {
+            assert!(import.starts_with("import type {"));
+            let stem = declarations.file_stem().unwrap().to_str().unwrap();
+            assert!(import.ends_with(&format!("from \"./{stem}\";")));
+            let content = read_to_string(declarations).unwrap();
+            for line in content.lines().filter(|line| line.starts_with("export")) {
+                assert!(
+                    line.starts_with("export interface ") || line.starts_with("export type "),
+                    "{line}"
+                );
+            }
+        }
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./types/index.ts\";"));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["devDependencies"]["typescript"].is_string());
+    }
+
+    #[test]
+    fn public_files_are_generated() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            public_file_count: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.public_files().len(), 10);
+        let assets = app.path().join("public/assets");
+        assert_eq!(std::fs::read_dir(&assets).unwrap().count(), 10);
+        let extensions = app
+            .public_files()
+            .iter()
+            .map(|file| file.extension().unwrap().to_str().unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(extensions, HashSet::from(["txt", "json", "svg", "png"]));
+        for file in app.public_files() {
+            assert!(file.starts_with(&assets));
+            if file.extension().unwrap() == "png" {
+                assert!(std::fs::read(file).unwrap().starts_with(b"\x89PNG"));
+            }
+        }
+    }
+
+    #[test]
+    fn css_import_chain_has_requested_depth() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            css_import_depth: 4,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let chain = app.css_import_chain();
+        assert_eq!(chain.len(), 4);
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./styles/import_0.css\";"));
+        for pair in chain.windows(2) {
+            let css = read_to_string(&pair[0]).unwrap();
+            let next = pair[1].file_name().unwrap().to_str().unwrap();
+            assert!(css.starts_with(&format!("@import \"./{next}\";")));
+        }
+        let last = read_to_string(chain.last().unwrap()).unwrap();
+        assert!(!last.contains("@import"));
+        assert!(last.contains('{') && last.contains(':'));
+    }
+
+    #[test]
+    fn css_theme_defines_requested_vars() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            css_theme_vars: 20,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let theme = read_to_string(app.path().join("src/styles/theme.css")).unwrap();
+        assert!(theme.starts_with(":root {"));
+        assert_eq!(
+            theme
+                .lines()
+                .filter(|line| line.trim_start().starts_with("--"))
+                .count(),
+            20
+        );
+        let components = read_to_string(app.path().join("src/styles/components.css")).unwrap();
+        assert!(components.contains("var(--theme-color-0)"));
+        let classes = app
+            .modules()
+            .iter()
+            .filter_map(|(module, _)| {
+                let content = read_to_string(module).unwrap();
+                let class = content.split("className=\"theme-").nth(1)?;
+                Some(class[..class.find('"').unwrap()].to_string())
+            })
+            .collect::<Vec<_>>();
+        assert!(classes.len() >= 2);
+        for class in classes {
+            assert!(components.contains(&format!(
+                ".theme-{class} polygon {{\n    fill: var(--theme-color-{class});"
+            )));
+        }
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./styles/theme.css\";"));
+    }
+
+    #[test]
+    fn metadata_routes_export_default_functions() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            route_groups: vec!["shop".to_string()],
+            metadata_routes: true,
+            typescript: true,
+            package_json: Some(Default::default()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let app_dir = app.path().join("src/app");
+        assert_eq!(
+            app.metadata_routes(),
+            [
+                app_dir.join("sitemap.ts"),
+                app_dir.join("robots.ts"),
+                app_dir.join("opengraph-image.tsx")
+            ]
+        );
+        let sitemap = read_to_string(app_dir.join("sitemap.ts")).unwrap();
+        assert!(sitemap.contains("export default function sitemap(): MetadataRoute.Sitemap {"));
+        assert!(sitemap.contains("{ url: \"https://example.com/group_0\""));
+        let robots = read_to_string(app_dir.join("robots.ts")).unwrap();
+        assert!(robots.contains("export default function robots(): MetadataRoute.Robots {"));
+        let opengraph_image = read_to_string(app_dir.join("opengraph-image.tsx")).unwrap();
+        assert!(opengraph_image.contains("export default function OpengraphImage() {"));
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["devDependencies"]["typescript"].is_string());
+
+        let app = TestAppBuilder {
+            module_count: 10,
+            metadata_routes: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        let sitemap = read_to_string(app.path().join("src/app/sitemap.js")).unwrap();
+        assert!(sitemap.starts_with("export default function sitemap() {"));
+    }
+
+    #[test]
+    fn preact_depends_on_preact_and_aliases_react() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            framework: Framework::Preact,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert_eq!(package_json["dependencies"]["preact"], "^10.11.3");
+        assert!(package_json["dependencies"].get("react").is_none());
+
+        for config in ["vite.config.js", "webpack.config.js"] {
+            let config = read_to_string(app.path().join(config)).unwrap();
+            assert!(config.contains(r#""react": "preact/compat""#));
+        }
+        let jsconfig: Value =
+            serde_json::from_str(&read_to_string(app.path().join("jsconfig.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            jsconfig["compilerOptions"]["paths"]["react"][0],
+            "./node_modules/preact/compat"
+        );
+    }
+
+    #[test]
+    fn tailwind_config_covers_sources_and_classes_are_used() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            tailwind: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let tailwind_config = read_to_string(app.path().join("tailwind.config.js")).unwrap();
+        assert!(tailwind_config.contains(r#"content: ["./src/**/*.{js,jsx,ts,tsx}"]"#));
+        assert!(app.path().join("postcss.config.js").exists());
+        let globals = read_to_string(app.path().join("src/globals.css")).unwrap();
+        assert!(globals.contains("@tailwind utilities;"));
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./globals.css\";"));
+        assert!(app
+            .modules()
+            .iter()
+            .any(|(module, _)| read_to_string(module)
+                .unwrap()
+                .contains("className=\"opacity-75")));
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["devDependencies"]["tailwindcss"].is_string());
+    }
+
+    #[test]
+    fn scss_files_use_nesting_and_shared_mixins() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            scss: true,
+            package_json: Some(Default::default()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let mixins = app.path().join("src/styles/_mixins.scss");
+        assert!(read_to_string(&mixins)
+            .unwrap()
+            .contains("@mixin interactive {"));
+        assert!(!app.scss_files().is_empty());
+        for scss_file in app.scss_files() {
+            let scss = read_to_string(scss_file).unwrap();
+            assert!(scss.starts_with("@use \""));
+            assert!(scss.contains("styles/_mixins.scss\" as mixins;"));
+            assert!(scss.contains("@include mixins.interactive;"));
+            assert!(scss.contains("    polygon {\n        @include mixins.tinted($color);"));
+            assert!(scss.contains("        &:hover {"));
+            let module = scss_file.with_extension("jsx");
+            let name = scss_file.file_name().unwrap().to_str().unwrap();
+            assert!(read_to_string(module)
+                .unwrap()
+                .contains(&format!("import \"./{name}\";")));
+        }
+
+        let package_json: Value =
+            serde_json::from_str(&read_to_string(app.path().join("package.json")).unwrap())
+                .unwrap();
+        assert!(package_json["devDependencies"]["sass"].is_string());
+    }
+
+    #[test]
+    fn dynamic_expressions_use_computed_specifiers() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            dynamic_expression_count: 4,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let index = read_to_string(app.path().join("src/dynamic/index.js")).unwrap();
+        assert_eq!(
+            index.matches("import(`./variant_${variant}.jsx`)").count(),
+            4
+        );
+        assert!(!app.dynamic_variants().is_empty());
+        for variant in app.dynamic_variants() {
+            assert!(variant.exists());
+            let name = variant.file_name().unwrap().to_str().unwrap();
+            assert!(name.starts_with("variant_") && name.ends_with(".jsx"));
+        }
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./dynamic/index.js\";"));
+    }
+
+    #[test]
+    fn workers_are_created_and_registered() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            shared_worker_count: 2,
+            audio_worklet_count: 3,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.shared_workers().len(), 2);
+        assert_eq!(app.audio_worklets().len(), 3);
+        let index = read_to_string(app.path().join("src/workers/index.js")).unwrap();
+        for worker in app.shared_workers() {
+            assert!(read_to_string(worker).unwrap().contains("self.onconnect"));
+            let name = worker.file_name().unwrap().to_str().unwrap();
+            assert!(index.contains(&format!("new SharedWorker(new URL(\"./{name}\"")));
+        }
+        for worklet in app.audio_worklets() {
+            assert!(read_to_string(worklet)
+                .unwrap()
+                .contains("registerProcessor("));
+            let name = worklet.file_name().unwrap().to_str().unwrap();
+            assert!(index.contains(&format!("audioWorklet.addModule(new URL(\"./{name}\"")));
+        }
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains("import \"./workers/index.js\";"));
+    }
+
+    #[test]
+    fn locales_are_generated_and_used() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            locales: vec!["en".to_string(), "de".to_string()],
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.locales().len(), 2);
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        for (locale, name) in app.locales().iter().zip(["en", "de"]) {
+            assert!(locale.exists());
+            let specifier = format!("locales/{name}.json\"");
+            assert!(modules.iter().any(|module| module.contains(&specifier)));
+        }
+    }
+}
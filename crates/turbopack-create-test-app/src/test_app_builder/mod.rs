@@ -0,0 +1,2186 @@
+mod components;
+mod entries;
+mod features;
+mod test_app;
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, rename, File},
+    hash::{Hash, Hasher},
+    io::prelude::*,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+
+pub use self::test_app::{TestApp, TreeStats, VerificationError};
+use self::{
+    components::{
+        astro_container, qwik_container, vue_container, web_component_container, write_container,
+        Leaves,
+    },
+    test_app::{TestAppTarget, VERIFIED_EXTENSIONS},
+};
+
+/// The number of messages in each generated locale catalog.
+const LOCALE_MESSAGE_COUNT: usize = 300;
+
+/// Deep subpaths imported instead of the package root by `resolve_real_deps`,
+/// by package name.
+const DEPENDENCY_SUBPATHS: [(&str, &str); 4] = [
+    ("lodash", "lodash/fp/compose"),
+    ("@babel/runtime", "@babel/runtime/helpers/esm/extends"),
+    ("date-fns", "date-fns/esm/addDays/index.js"),
+    ("rxjs", "rxjs/operators"),
+];
+
+/// The name of the workspace package generated for `subpath_exports`.
+const SHARED_PACKAGE_NAME: &str = "@turbopack-test-app/shared";
+
+/// Every n-th theme custom property is referenced by a rule in
+/// `src/styles/components.css` when `css_theme_vars` is set.
+const THEME_VAR_USE_INTERVAL: usize = 3;
+
+/// The builtins imported by the server modules with `node_builtin_imports`,
+/// as pairs of a `node:` specifier and one of its exports.
+const NODE_BUILTINS: [(&str, &str); 8] = [
+    ("node:fs/promises", "readFile"),
+    ("node:path", "join"),
+    ("node:os", "hostname"),
+    ("node:crypto", "randomUUID"),
+    ("node:url", "fileURLToPath"),
+    ("node:util", "inspect"),
+    ("node:zlib", "gzipSync"),
+    ("node:buffer", "Buffer"),
+];
+
+/// The extensions used for leaf modules with `extension_mix`.
+const MIXED_LEAF_EXTENSIONS: [&str; 6] = ["js", "jsx", "ts", "tsx", "mjs", "cjs"];
+
+/// The extensions used for modules with children with `extension_mix`, all of
+/// which allow JSX.
+const MIXED_CONTAINER_EXTENSIONS: [&str; 3] = ["js", "jsx", "tsx"];
+
+/// Appended to every component module with `realistic_boilerplate`. It's only
+/// declared, so the rendered output doesn't change.
+const REALISTIC_BOILERPLATE: &str = r#"
+/**
+ * Layout constants shared by the helpers below.
+ */
+const TRIANGLE_SIDE = 10;
+const TRIANGLE_HEIGHT = (TRIANGLE_SIDE * Math.sqrt(3)) / 2;
+const DEFAULT_FILL = "white";
+
+/**
+ * Clamps `value` into the range `[min, max]`.
+ *
+ * @param {number} value The value to clamp.
+ * @param {number} min The lower bound.
+ * @param {number} max The upper bound.
+ * @returns {number} The clamped value.
+ */
+function clampToRange(value, min, max) {
+    return Math.min(Math.max(value, min), max);
+}
+
+/**
+ * Formats the corners of a triangle scaled by `scale` as an SVG `points`
+ * attribute.
+ *
+ * @param {number} scale The scale factor, clamped to `[0, 1]`.
+ * @returns {string} The formatted points.
+ */
+function formatTrianglePoints(scale) {
+    const factor = clampToRange(scale, 0, 1);
+    const halfSide = (TRIANGLE_SIDE / 2) * factor;
+    const halfHeight = (TRIANGLE_HEIGHT / 2) * factor;
+    return [
+        [-halfSide, halfHeight],
+        [0, -halfHeight],
+        [halfSide, halfHeight],
+    ]
+        .map(([x, y]) => `${x.toFixed(2)},${y.toFixed(2)}`)
+        .join(" ");
+}
+
+/**
+ * Merges a style object over the default triangle style.
+ *
+ * @param {Record<string, string> | undefined} style The style to apply.
+ * @returns {Record<string, string>} The merged style.
+ */
+function resolveTriangleStyle(style) {
+    return { fill: DEFAULT_FILL, ...style };
+}
+"#;
+
+/// Returns an import specifier for `to` relative to the directory containing
+/// `from`.
+fn relative_import(from: &Path, to: &Path) -> String {
+    let from = from.parent().unwrap().components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut specifier = if common == from.len() {
+        ".".to_string()
+    } else {
+        vec![".."; from.len() - common].join("/")
+    };
+    for component in &to[common..] {
+        if let Component::Normal(name) = component {
+            specifier.push('/');
+            specifier.push_str(name.to_str().unwrap());
+        }
+    }
+    specifier
+}
+
+/// Rewrites the trailing `export default React.memo(Component);` of a React
+/// template into a named `Triangle` export.
+fn into_named_export(content: String) -> String {
+    const DEFAULT_EXPORT: &str = "export default React.memo(";
+    match content.rfind(DEFAULT_EXPORT) {
+        Some(index) => {
+            let component = content[index + DEFAULT_EXPORT.len()..]
+                .split(')')
+                .next()
+                .unwrap();
+            format!(
+                "{}const Memo{component} = React.memo({component});\nexport {{ Memo{component} as \
+                 Triangle }};\n",
+                &content[..index]
+            )
+        }
+        None => content,
+    }
+}
+
+/// Resolves the `.` and `..` components of `path` without accessing the
+/// filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Collects the files below `dir` into `files`. A missing `dir` has no files.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in read_dir(dir).context("reading dir")? {
+        let path = entry.context("reading dir entry")?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Collects `dir` and the files and directories below it into `entries`. A
+/// missing `dir` has no entries.
+fn collect_entries(dir: &Path, entries: &mut HashSet<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    entries.insert(dir.to_path_buf());
+    for entry in read_dir(dir).context("reading dir")? {
+        let path = entry.context("reading dir entry")?.path();
+        if path.is_dir() {
+            collect_entries(&path, entries)?;
+        } else {
+            entries.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// Removes the files and directories in `dir`, including `dir` itself, that
+/// aren't part of the `existing` entries.
+fn remove_created_entries(dir: &Path, existing: &HashSet<PathBuf>) -> Result<()> {
+    if !existing.contains(dir) {
+        return remove_dir_all(dir).context("removing created dir");
+    }
+    for entry in read_dir(dir).context("reading dir")? {
+        let path = entry.context("reading dir entry")?.path();
+        if path.is_dir() {
+            remove_created_entries(&path, existing)?;
+        } else if !existing.contains(&path) {
+            remove_file(&path).context("removing created file")?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the relative specifiers in the `content` of `file` that resolve to
+/// a module renamed by `renames`. Specifiers without an extension stay without
+/// an extension.
+fn rewrite_specifiers(file: &Path, content: &str, renames: &HashMap<PathBuf, PathBuf>) -> String {
+    let extensionless = renames
+        .iter()
+        .map(|(from, to)| (from.with_extension(""), to.with_extension("")))
+        .collect::<HashMap<_, _>>();
+    let dir = file.parent().unwrap();
+    let mut rewritten = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find(['"', '\'']) {
+        let quote = &rest[start..start + 1];
+        let (before, after) = rest.split_at(start + 1);
+        rewritten.push_str(before);
+        rest = after;
+        if !rest.starts_with("./") && !rest.starts_with("../") {
+            continue;
+        }
+        let end = match rest.find(quote) {
+            Some(end) => end,
+            None => continue,
+        };
+        let specifier = &rest[..end];
+        if specifier.contains(char::is_whitespace) {
+            continue;
+        }
+        let resolved = normalize(&dir.join(specifier));
+        if let Some(to) = renames
+            .get(&resolved)
+            .or_else(|| extensionless.get(&resolved))
+        {
+            rewritten.push_str(&relative_import(file, to));
+            rest = &rest[end..];
+        }
+    }
+    rewritten.push_str(rest);
+    rewritten
+}
+
+/// Returns `count` imports of [NODE_BUILTINS], cycling through them, together
+/// with a statement referencing the imported bindings so they are kept.
+fn node_builtin_imports(count: usize) -> (String, String) {
+    let mut imports = String::new();
+    let mut bindings = Vec::with_capacity(count);
+    for (i, (specifier, export)) in NODE_BUILTINS.iter().cycle().take(count).enumerate() {
+        imports.push_str(&format!(
+            "import {{ {export} as {export}{i} }} from \"{specifier}\";\n"
+        ));
+        bindings.push(format!("{export}{i}"));
+    }
+    let usage = format!("const builtins = [{}];\n", bindings.join(", "));
+    (imports, usage)
+}
+
+fn decide(remaining: usize, min_remaining_decisions: usize) -> bool {
+    if remaining == 0 {
+        false
+    } else if min_remaining_decisions <= remaining {
+        true
+    } else {
+        let urgentness = min_remaining_decisions / remaining;
+        (min_remaining_decisions * 11 * 7 * 5) % urgentness == 0
+    }
+}
+
+fn decide_early(remaining: usize, min_remaining_decisions: usize) -> bool {
+    if remaining == 0 {
+        false
+    } else if min_remaining_decisions <= remaining {
+        true
+    } else {
+        let urgentness = min_remaining_decisions / remaining / remaining;
+        (min_remaining_decisions * 11 * 7 * 5) % urgentness == 0
+    }
+}
+
+/// The UI framework the generated app is written for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Framework {
+    #[default]
+    React,
+    /// Uses the React templates with `react` aliased to `preact/compat`.
+    Preact,
+    /// Generates `.tsx` Qwik components using `component$` and Qwik City
+    /// routing. Qwik loads components lazily by default, so no dynamic
+    /// imports are generated. Options that only apply to the React templates,
+    /// e.g. `locales` or `unit_tests`, are ignored.
+    Qwik,
+    /// Generates SolidJS components, dynamic imports use Solid's `lazy` and
+    /// `Suspense`. Options that only apply to the React templates are ignored.
+    Solid,
+    /// Generates server-only `.astro` components. The dynamic imports become
+    /// React islands hydrated with `client:load`, which are always leaves.
+    Astro,
+    /// Uses the React templates and generates Remix route modules in
+    /// `app/routes` with a `loader`, one for the root module and one for each
+    /// of its children.
+    Remix,
+    /// Generates framework-free custom elements, each rendering its children
+    /// or its triangle into a shadow root. Dynamically imported elements are
+    /// upgraded once their module is loaded. Options that only apply to the
+    /// React templates are ignored.
+    WebComponents,
+    /// Generates Vue single-file components using `<script setup>`, dynamic
+    /// imports use `defineAsyncComponent`. With `typescript`, the props are
+    /// typed via `defineProps<...>()`. Options that only apply to the React
+    /// templates are ignored.
+    Vue,
+}
+
+impl Framework {
+    /// The extension of the generated component modules.
+    fn extension(self) -> &'static str {
+        match self {
+            Framework::React | Framework::Preact | Framework::Solid | Framework::Remix => "jsx",
+            Framework::Qwik => "tsx",
+            Framework::Astro => "astro",
+            Framework::WebComponents => "js",
+            Framework::Vue => "vue",
+        }
+    }
+
+    /// The command building the app for production.
+    fn build_command(self) -> &'static str {
+        match self {
+            Framework::React => "next build",
+            Framework::Preact
+            | Framework::Qwik
+            | Framework::Solid
+            | Framework::WebComponents
+            | Framework::Vue => "vite build",
+            Framework::Astro => "astro build",
+            Framework::Remix => "remix build",
+        }
+    }
+
+    /// The command serving the production build.
+    fn start_command(self) -> &'static str {
+        match self {
+            Framework::React => "next start",
+            Framework::Preact
+            | Framework::Qwik
+            | Framework::Solid
+            | Framework::WebComponents
+            | Framework::Vue => "vite preview --host",
+            Framework::Astro => "astro preview --host",
+            Framework::Remix => "remix-serve build",
+        }
+    }
+
+    /// Whether the modules are generated from the React templates, which all
+    /// template options apply to.
+    fn uses_react_templates(self) -> bool {
+        matches!(
+            self,
+            Framework::React | Framework::Preact | Framework::Remix
+        )
+    }
+
+    /// The leaf module of frameworks that don't use the React templates.
+    /// [Framework::WebComponents] leaves depend on their element name and
+    /// [Framework::Vue] leaves on `typescript`, so they are written by
+    /// `web_component_leaf` and `vue_leaf` instead.
+    fn native_leaf(self) -> Option<&'static str> {
+        match self {
+            Framework::React
+            | Framework::Preact
+            | Framework::Remix
+            | Framework::WebComponents
+            | Framework::Vue => None,
+            Framework::Qwik => Some(
+                r#"import { component$ } from "@builder.io/qwik";
+
+export default component$((props: { style: Record<string, string> }) => {
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={props.style} />;
+});
+"#,
+            ),
+            Framework::Astro => Some(
+                r#"---
+const { style } = Astro.props;
+---
+<polygon points="-5,4.33 0,-4.33 5,4.33" style={style} />
+"#,
+            ),
+            Framework::Solid => Some(
+                r#"import { mergeProps } from "solid-js";
+
+export default function Triangle(props) {
+    const merged = mergeProps({ style: {} }, props);
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={merged.style} />;
+}
+"#,
+            ),
+        }
+    }
+}
+
+/// How the modules with children of the React templates wrap their children.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FragmentStyle {
+    /// Uses the `<>...</>` shorthand.
+    #[default]
+    Shorthand,
+    /// Uses `<React.Fragment>...</React.Fragment>`.
+    Explicit,
+    /// Alternates between both styles, starting with the shorthand.
+    Mixed,
+}
+
+impl FragmentStyle {
+    /// The opening and closing fragment tags of the `index`-th module with
+    /// children.
+    fn tags(self, index: usize) -> (&'static str, &'static str) {
+        let explicit = match self {
+            FragmentStyle::Shorthand => false,
+            FragmentStyle::Explicit => true,
+            FragmentStyle::Mixed => index % 2 == 1,
+        };
+        if explicit {
+            ("<React.Fragment>", "</React.Fragment>")
+        } else {
+            ("<>", "</>")
+        }
+    }
+}
+
+/// Builds a test app, i.e. a tree of `module_count` component modules
+/// rendering triangles, and the additional files enabled by the options.
+///
+/// # Leaf features
+///
+/// Several options change what a leaf module renders. Each leaf module uses at
+/// most one of these leaf features. When several of them apply to the same
+/// leaf module, the first one in this list wins:
+///
+/// 1. `forward_ref_ratio`
+/// 2. the `.cjs`, `.ts` and `.mjs` extensions of `extension_mix`
+/// 3. `locales`
+/// 4. `import_meta_env_count`
+/// 5. `graphql_import_count`
+/// 6. `json_import_count`
+/// 7. `deep_expression_count`
+/// 8. `diamond_count`
+/// 9. `portal_count`
+/// 10. `tailwind`
+/// 11. `styled_components`
+/// 12. `scss`
+/// 13. `css_file_count`
+/// 14. `resolve_real_deps`
+/// 15. `subpath_exports`
+/// 16. `css_theme_vars`
+///
+/// The features with a count use the next leaf modules that no earlier
+/// feature took, so they only fall short of their count when the tree has too
+/// few leaf modules. The accessors of [TestApp] return what was actually
+/// generated, e.g. [TestApp::portals]. The other features apply to every few
+/// leaf modules and skip the ones taken by an earlier feature. `unit_tests`
+/// combines with all of them. The leaf features only apply to the React
+/// templates, i.e. not to the leaf modules of [Framework::Qwik],
+/// [Framework::Solid], [Framework::WebComponents], [Framework::Vue] and the
+/// `.astro` leaf modules of [Framework::Astro].
+#[derive(Debug)]
+pub struct TestAppBuilder {
+    pub target: Option<PathBuf>,
+    /// Removes the files and directories created by a failed build from
+    /// `target`, which is the default. Entries that existed before the build
+    /// are left in place, though files it already overwrote keep their new
+    /// content. Temporary targets are always removed when the build fails.
+    pub cleanup_on_error: bool,
+    pub module_count: usize,
+    pub directories_count: usize,
+    pub dynamic_import_count: usize,
+    pub flatness: usize,
+    /// The flatness to use at each depth (the root module has depth 0), the
+    /// last value is used for all deeper levels. Falls back to `flatness`
+    /// when empty.
+    pub flatness_profile: Vec<usize>,
+    /// The fraction (between 0 and 1) of modules that use a named `Triangle`
+    /// export instead of a default export. The root module always uses a
+    /// default export.
+    pub named_export_ratio: f32,
+    /// The fraction (between 0 and 1) of modules that are wrapped in
+    /// `React.forwardRef`, with their parent passing a ref created by
+    /// `React.useRef`. Every other leaf module among them exposes a handle via
+    /// `React.useImperativeHandle` instead of forwarding the ref to its
+    /// polygon. The root module never forwards a ref. Only applies to the
+    /// React templates and is ignored with `extension_mix`.
+    /// A leaf feature, see [TestAppBuilder].
+    pub forward_ref_ratio: f32,
+    /// Modules at this depth (the root module has depth 0) are always leaves.
+    pub max_depth: Option<usize>,
+    /// Generates a message catalog in `src/locales` for each locale, which is
+    /// used by a subset of the leaf modules.
+    /// A leaf feature, see [TestAppBuilder].
+    pub locales: Vec<String>,
+    /// Writes a `.gitignore` and a `README.md` describing the generation
+    /// parameters, so the app can be committed e.g. for bug reproductions.
+    pub scaffold_repo: bool,
+    /// The number of additional `pages/page_N.jsx` pages, each rendering a
+    /// different component of the tree.
+    pub pages_router_page_count: usize,
+    /// The number of leaf modules reading a `VITE_*` variable via
+    /// `import.meta.env`. The variables are defined in a generated `.env`.
+    /// A leaf feature, see [TestAppBuilder].
+    pub import_meta_env_count: usize,
+    /// The number of `NEXT_PUBLIC_*` variables a generated `next.config.js`
+    /// requires, failing the build when one of them is missing. The variables
+    /// are defined in the generated `.env`, next to the ones of
+    /// `import_meta_env_count`.
+    pub next_public_env_count: usize,
+    /// The number of leaf modules importing a `.graphql` query document.
+    /// A leaf feature, see [TestAppBuilder].
+    pub graphql_import_count: usize,
+    /// The number of leaf modules also rendering into a portal, i.e. a
+    /// `ReactDOM.createPortal` target appended to `document.body`. The portals
+    /// are only created on the client, so server rendering still works.
+    /// A leaf feature, see [TestAppBuilder].
+    pub portal_count: usize,
+    /// The number of diamond shapes in the module graph. Each diamond is a
+    /// shared module in `src/diamonds` imported by two leaf modules, which
+    /// share a common ancestor in the tree, so bundlers have to deduplicate
+    /// it. The shared modules have no imports, so no cycles are introduced.
+    /// Needs two leaf modules per diamond.
+    /// A leaf feature, see [TestAppBuilder].
+    pub diamond_count: usize,
+    /// The number of leaf modules returning a conditional expression nested
+    /// `deep_expression_depth` levels deep, to stress parsing and scope
+    /// analysis. A leaf feature, see [TestAppBuilder].
+    pub deep_expression_count: usize,
+    /// The nesting depth of the conditional expressions of
+    /// `deep_expression_count`.
+    pub deep_expression_depth: usize,
+    /// The number of leaf modules importing a `.json` data file.
+    /// A leaf feature, see [TestAppBuilder].
+    pub json_import_count: usize,
+    /// Imports the `.json` data files of `json_import_count` with the
+    /// `with { type: "json" }` import attribute.
+    pub json_import_attributes: bool,
+    /// Generates a Next.js `middleware.js` in the root of the app.
+    pub middleware: bool,
+    /// The number of `"use server"` actions generated in
+    /// `src/app/forms/actions.js`, each submitted by a form of the
+    /// `src/app/forms/page.jsx` app router page.
+    pub server_actions: usize,
+    /// The number of app router segments nested below `src/app/nested`, each
+    /// with a `layout.jsx` wrapping its children and rendering a component of
+    /// the tree. The innermost segment has a `page.jsx`.
+    pub nested_layouts: usize,
+    /// Generates an app router route group `src/app/(group)` for each name,
+    /// which only organizes the routes without affecting their URLs. Each
+    /// group contains a `group_N/page.jsx` rendering a component of the tree,
+    /// so the pages of different groups don't resolve to the same URL. Every
+    /// other group, starting with the first one, also has a `layout.jsx`.
+    pub route_groups: Vec<String>,
+    /// Generates the app router metadata routes `src/app/sitemap`,
+    /// `src/app/robots` and `src/app/opengraph-image`, which are compiled
+    /// into routes by Next.js. They are written in TypeScript with
+    /// `typescript`, otherwise in JavaScript.
+    pub metadata_routes: bool,
+    /// The number of `node:` builtin imports of each server module, i.e. the
+    /// `src/pages/api/builtins.js` API route of the React templates and the
+    /// route modules of [Framework::Remix], where they are used by the
+    /// `loader`. Client components never import them.
+    pub node_builtin_imports: usize,
+    /// The number of dynamic `import()`s with a template literal specifier in
+    /// `src/dynamic/index.js`, each selecting one of the sibling
+    /// `variant_N.jsx` modules at runtime. Bundlers can't resolve these
+    /// statically and have to include every candidate. The index is imported
+    /// by the bootstrap of [Framework::React] and [Framework::Preact].
+    pub dynamic_expression_count: usize,
+    /// The number of `SharedWorker`s in `src/workers`, each started with
+    /// `new SharedWorker(new URL(...))` by `src/workers/index.js`.
+    pub shared_worker_count: usize,
+    /// The number of audio worklet processors in `src/workers`, each added via
+    /// `audioWorklet.addModule(new URL(...))` by `src/workers/index.js`. The
+    /// index is imported by the bootstrap of [Framework::React] and
+    /// [Framework::Preact].
+    pub audio_worklet_count: usize,
+    /// The number of TypeScript classes in `src/decorators` using legacy
+    /// decorators, together with a `tsconfig.json` enabling
+    /// `experimentalDecorators`. The classes are imported by the bootstrap of
+    /// [Framework::React] and [Framework::Preact]. Requires `typescript`,
+    /// building fails otherwise.
+    pub decorators: usize,
+    /// The number of `import type` declarations in `src/types/index.ts`, each
+    /// importing from a `.ts` module in `src/types` which only declares types,
+    /// so the imports have to be elided completely. The index is imported by
+    /// the bootstrap of [Framework::React] and [Framework::Preact]. Requires
+    /// `typescript`, building fails otherwise.
+    pub type_only_import_count: usize,
+    /// The length of a chain of CSS files in `src/styles`, each `@import`-ing
+    /// the next one. The head of the chain is imported by the bootstrap.
+    pub css_import_depth: usize,
+    /// The number of custom properties defined on `:root` by
+    /// `src/styles/theme.css`. A subset of them is referenced via `var()` by
+    /// the `.theme-N` rules of `src/styles/components.css`, and every few leaf
+    /// modules use one of these classes, taking turns. Both stylesheets are
+    /// imported by the bootstrap. A leaf feature, see [TestAppBuilder].
+    pub css_theme_vars: usize,
+    pub framework: Framework,
+    /// Writes the `<script setup>` blocks of [Framework::Vue] in TypeScript,
+    /// typing the props via `defineProps<...>()`, and the bootstrap as
+    /// `src/main.ts`. Also writes the `metadata_routes` in TypeScript and is
+    /// required by `decorators` and `type_only_import_count`.
+    pub typescript: bool,
+    /// How the modules with children of the React templates wrap their
+    /// children, see [FragmentStyle].
+    pub fragment_style: FragmentStyle,
+    /// Generates a Tailwind and PostCSS config, imports the `@tailwind`
+    /// directives from the bootstrap and uses utility classes in a subset of
+    /// the leaf modules. A leaf feature, see [TestAppBuilder].
+    pub tailwind: bool,
+    /// Renders `styled.g` and `styled.polygon` wrappers from
+    /// `styled-components` in a subset of the leaf modules and adds the
+    /// dependency to the `package.json`. A leaf feature, see [TestAppBuilder].
+    pub styled_components: bool,
+    /// Generates a `.scss` file using variables and nesting for a subset of
+    /// the leaf modules, which import it. Each file includes the mixins of a
+    /// shared `src/styles/_mixins.scss` via `@use`. Only applies to the React
+    /// templates. A leaf feature, see [TestAppBuilder].
+    pub scss: bool,
+    /// The number of leaf modules importing a sibling `.css` file. A
+    /// `css_module_ratio` fraction of them imports it as a CSS module, e.g.
+    /// `import styles from "./triangle_1.module.css"`, the others as a
+    /// side-effect import, so both ways of loading CSS are exercised by the
+    /// same app. A leaf feature, see [TestAppBuilder].
+    pub css_file_count: usize,
+    /// The fraction of the `css_file_count` stylesheets imported as a CSS
+    /// module.
+    pub css_module_ratio: f32,
+    /// Generates a workspace package in `packages/shared` with an `exports`
+    /// map of several subpaths, one of them conditional, and an `imports` map
+    /// for `#internal/*`. A subset of the leaf modules imports the package
+    /// via a subpath. There is no separate monorepo layout, the package is a
+    /// workspace of the app itself. Only applies to the React templates.
+    /// A leaf feature, see [TestAppBuilder].
+    pub subpath_exports: bool,
+    /// Every few leaf modules import one of the `extra_dependencies` of the
+    /// [PackageJsonConfig], taking turns. Well-known packages like `lodash`
+    /// are imported via a deep subpath, e.g. `lodash/fp/compose`, other
+    /// packages via their name, e.g. `@scope/pkg`. Only applies to the React
+    /// templates and does nothing without `extra_dependencies`.
+    /// A leaf feature, see [TestAppBuilder].
+    pub resolve_real_deps: bool,
+    /// Gives each `React.Suspense` of a dynamic import a fallback, rendered by
+    /// the shared `src/fallback.jsx` module.
+    pub suspense_fallback: bool,
+    /// The number of class-based error boundaries in `src/error_boundaries`,
+    /// each wrapping the first child of a different container module. Only
+    /// applies to the React templates.
+    pub error_boundaries: usize,
+    /// Renders a component throwing on its first render inside the first
+    /// error boundary, so its fallback is rendered.
+    pub error_boundary_throw: bool,
+    /// Generates a `*.test.jsx` snapshot test next to a subset of the leaf
+    /// modules.
+    pub unit_tests: bool,
+    /// Appends constants and JSDoc-annotated helper functions to each
+    /// component module, to model the parsing cost of real code.
+    pub realistic_boilerplate: bool,
+    /// Prepends the given text as a `/*! ... */` comment to every component
+    /// module. Minifiers preserve these comments, so they affect the output
+    /// and its source map offsets.
+    pub license_header: Option<String>,
+    /// Names the component modules after their depth, their role and their
+    /// index, e.g. `d2_container_005.jsx` or `d5_leaf_123.jsx`, instead of
+    /// their position in the tree.
+    pub descriptive_names: bool,
+    /// Spreads the modules (except the root module) over the `.js`, `.jsx`,
+    /// `.ts`, `.tsx`, `.mjs` and `.cjs` extensions, so the extensionless
+    /// imports have to probe different extensions. The extension only depends
+    /// on the position of the module in the tree. Leaf modules with an
+    /// extension that doesn't allow JSX use `React.createElement` instead.
+    /// Only applies to the React templates.
+    /// A leaf feature, see [TestAppBuilder].
+    pub extension_mix: bool,
+    /// Generates a multi-stage `Dockerfile` running the `build` script of the
+    /// `package.json`, and a `.dockerignore`. The `build` script defaults to
+    /// the build command of the framework.
+    pub dockerfile: bool,
+    /// The number of static files in `public/assets`, alternating between
+    /// `.txt`, `.json`, `.svg` and `.png` files. They aren't imported by any
+    /// module.
+    pub public_file_count: usize,
+    pub package_json: Option<PackageJsonConfig>,
+}
+
+impl Default for TestAppBuilder {
+    fn default() -> Self {
+        Self {
+            target: None,
+            cleanup_on_error: true,
+            module_count: 1000,
+            directories_count: 50,
+            dynamic_import_count: 0,
+            flatness: 5,
+            flatness_profile: Vec::new(),
+            named_export_ratio: 0.0,
+            forward_ref_ratio: 0.0,
+            max_depth: None,
+            locales: Vec::new(),
+            scaffold_repo: false,
+            pages_router_page_count: 0,
+            import_meta_env_count: 0,
+            next_public_env_count: 0,
+            graphql_import_count: 0,
+            portal_count: 0,
+            diamond_count: 0,
+            deep_expression_count: 0,
+            deep_expression_depth: 50,
+            json_import_count: 0,
+            json_import_attributes: false,
+            middleware: false,
+            server_actions: 0,
+            nested_layouts: 0,
+            route_groups: Vec::new(),
+            metadata_routes: false,
+            node_builtin_imports: 0,
+            dynamic_expression_count: 0,
+            shared_worker_count: 0,
+            audio_worklet_count: 0,
+            decorators: 0,
+            type_only_import_count: 0,
+            css_import_depth: 0,
+            css_theme_vars: 0,
+            framework: Framework::React,
+            typescript: false,
+            fragment_style: FragmentStyle::Shorthand,
+            tailwind: false,
+            styled_components: false,
+            scss: false,
+            css_file_count: 0,
+            css_module_ratio: 0.5,
+            subpath_exports: false,
+            resolve_real_deps: false,
+            suspense_fallback: false,
+            error_boundaries: 0,
+            error_boundary_throw: false,
+            unit_tests: false,
+            realistic_boilerplate: false,
+            license_header: None,
+            descriptive_names: false,
+            extension_mix: false,
+            dockerfile: false,
+            public_file_count: 0,
+            package_json: Some(Default::default()),
+        }
+    }
+}
+
+/// Returns the name of the custom element defined by the module at `file`,
+/// derived from its path relative to `src`, e.g. `x-triangle-triangle-2` for
+/// `src/triangle/triangle_2.js`.
+fn custom_element_name(src: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(src).unwrap().with_extension("");
+    let name = relative
+        .to_str()
+        .unwrap()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("x-{name}")
+}
+
+/// Returns the specifier importing the package `name`, a deep subpath for the
+/// packages of [DEPENDENCY_SUBPATHS].
+fn dependency_specifier(name: &str) -> String {
+    DEPENDENCY_SUBPATHS
+        .iter()
+        .find(|(package, _)| *package == name)
+        .map_or(name, |(_, subpath)| subpath)
+        .to_string()
+}
+
+/// Returns how many modules can still be placed below a module at `depth`
+/// without exceeding `max_depth`.
+fn subtree_capacity(depth: usize, max_depth: Option<usize>) -> usize {
+    match max_depth {
+        Some(max_depth) => (depth..max_depth).fold(0, |capacity: usize, _| {
+            capacity.saturating_add(1).saturating_mul(3)
+        }),
+        None => usize::MAX,
+    }
+}
+
+impl TestAppBuilder {
+    pub fn build(&self) -> Result<TestApp> {
+        let target = if let Some(target) = self.target.clone() {
+            TestAppTarget::Set(target)
+        } else {
+            TestAppTarget::Temp(tempfile::tempdir().context("creating tempdir")?)
+        };
+        self.build_target(target)
+    }
+
+    /// Builds the app into `dir` instead of `target`. Existing files in `dir`
+    /// are overwritten, like when building into `target`.
+    pub fn build_into(&self, dir: &Path) -> Result<TestApp> {
+        self.build_target(TestAppTarget::Set(dir.to_path_buf()))
+    }
+
+    /// Returns a fingerprint of the options affecting the generated app, i.e.
+    /// all options except `target`, e.g. to cache generated apps. The hash is
+    /// stable for a given build of this crate.
+    pub fn config_hash(&self) -> u64 {
+        // Destructured, so new options can't be forgotten here.
+        let TestAppBuilder {
+            target: _,
+            cleanup_on_error: _,
+            module_count,
+            directories_count,
+            dynamic_import_count,
+            flatness,
+            flatness_profile,
+            named_export_ratio,
+            forward_ref_ratio,
+            max_depth,
+            locales,
+            scaffold_repo,
+            pages_router_page_count,
+            import_meta_env_count,
+            next_public_env_count,
+            graphql_import_count,
+            portal_count,
+            diamond_count,
+            deep_expression_count,
+            deep_expression_depth,
+            json_import_count,
+            json_import_attributes,
+            middleware,
+            server_actions,
+            nested_layouts,
+            route_groups,
+            metadata_routes,
+            node_builtin_imports,
+            dynamic_expression_count,
+            shared_worker_count,
+            audio_worklet_count,
+            decorators,
+            type_only_import_count,
+            css_import_depth,
+            css_theme_vars,
+            framework,
+            typescript,
+            fragment_style,
+            tailwind,
+            styled_components,
+            scss,
+            css_file_count,
+            css_module_ratio,
+            subpath_exports,
+            resolve_real_deps,
+            suspense_fallback,
+            error_boundaries,
+            error_boundary_throw,
+            unit_tests,
+            realistic_boilerplate,
+            license_header,
+            descriptive_names,
+            extension_mix,
+            dockerfile,
+            public_file_count,
+            package_json,
+        } = self;
+        let fields: [&dyn Debug; 54] = [
+            module_count,
+            directories_count,
+            dynamic_import_count,
+            flatness,
+            flatness_profile,
+            named_export_ratio,
+            forward_ref_ratio,
+            max_depth,
+            locales,
+            scaffold_repo,
+            pages_router_page_count,
+            import_meta_env_count,
+            next_public_env_count,
+            graphql_import_count,
+            portal_count,
+            diamond_count,
+            deep_expression_count,
+            deep_expression_depth,
+            json_import_count,
+            json_import_attributes,
+            middleware,
+            server_actions,
+            nested_layouts,
+            route_groups,
+            metadata_routes,
+            node_builtin_imports,
+            dynamic_expression_count,
+            shared_worker_count,
+            audio_worklet_count,
+            decorators,
+            type_only_import_count,
+            css_import_depth,
+            css_theme_vars,
+            framework,
+            typescript,
+            fragment_style,
+            tailwind,
+            styled_components,
+            scss,
+            css_file_count,
+            css_module_ratio,
+            subpath_exports,
+            resolve_real_deps,
+            suspense_fallback,
+            error_boundaries,
+            error_boundary_throw,
+            unit_tests,
+            realistic_boilerplate,
+            license_header,
+            descriptive_names,
+            extension_mix,
+            dockerfile,
+            public_file_count,
+            package_json,
+        ];
+        let mut hasher = DefaultHasher::new();
+        for field in fields {
+            format!("{field:?}").hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn build_target(&self, target: TestAppTarget) -> Result<TestApp> {
+        if self.decorators > 0 && !self.typescript {
+            bail!("decorators require typescript");
+        }
+        if self.type_only_import_count > 0 && !self.typescript {
+            bail!("type-only imports require typescript");
+        }
+        let existing = match &target {
+            TestAppTarget::Set(dir) if self.cleanup_on_error => {
+                let mut existing = HashSet::new();
+                collect_entries(dir, &mut existing).context("collecting existing entries")?;
+                Some((dir.clone(), existing))
+            }
+            _ => None,
+        };
+        let result = self.write_app(target);
+        if let (Err(_), Some((dir, existing))) = (&result, existing) {
+            // Cleaning up is best effort, the build error is more relevant.
+            let _ = remove_created_entries(&dir, &existing);
+        }
+        result
+    }
+
+    fn write_app(&self, target: TestAppTarget) -> Result<TestApp> {
+        let mut app = TestApp {
+            target,
+            requested_module_count: self.module_count,
+            modules: Vec::new(),
+            lazy_modules: Vec::new(),
+            eager_modules: Vec::new(),
+            unplaced_modules: 0,
+            tree_stats: TreeStats::default(),
+            locales: Vec::new(),
+            pages: Vec::new(),
+            graphql_documents: Vec::new(),
+            json_files: Vec::new(),
+            portals: Vec::new(),
+            diamonds: Vec::new(),
+            deep_expressions: Vec::new(),
+            decorated_modules: Vec::new(),
+            type_declarations: Vec::new(),
+            dynamic_variants: Vec::new(),
+            shared_workers: Vec::new(),
+            audio_worklets: Vec::new(),
+            error_boundaries: Vec::new(),
+            nested_layouts: Vec::new(),
+            route_groups: Vec::new(),
+            metadata_routes: Vec::new(),
+            public_files: Vec::new(),
+            css_import_chain: Vec::new(),
+            scss_files: Vec::new(),
+            css_files: Vec::new(),
+            unit_tests: Vec::new(),
+            named_exports: HashSet::new(),
+            additional_files: Vec::new(),
+        };
+        let path = app.path().to_path_buf();
+        let src = path.join("src");
+        create_dir_all(&src).context("creating src dir")?;
+
+        if !self.locales.is_empty() {
+            self.write_locales(&mut app, &src)?;
+        }
+        let real_dependencies = match &self.package_json {
+            Some(package_json) if self.resolve_real_deps => package_json
+                .extra_dependencies
+                .keys()
+                .map(|name| dependency_specifier(name))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut leaves = Leaves {
+            src: src.clone(),
+            count: 0,
+            env_vars: Vec::new(),
+            forward_refs: 0,
+            diamonds: 0,
+            css_modules: 0,
+            real_dependencies,
+            real_dependency_imports: 0,
+            theme_classes: 0,
+        };
+        if self.scss && self.framework.uses_react_templates() {
+            self.write_scss_mixins(&src)?;
+        }
+
+        let mut remaining_modules = self.module_count - 1;
+        let mut remaining_directories = self.directories_count;
+        let mut remaining_dynamic_imports = self.dynamic_import_count;
+        // Modules that are imported via `React.lazy`/`import()` by their parent.
+        let mut lazy_files = HashSet::new();
+        let mut containers = HashSet::new();
+        let mut max_fanout = 0;
+        let mut export_decisions = 0;
+        // Modules that are wrapped in `React.forwardRef`, decided by their
+        // parent, which passes them a ref.
+        let mut forward_refs = HashSet::new();
+
+        let fallback_file = src.join("fallback.jsx");
+        if self.suspense_fallback {
+            let fallback = r#"import React from "react";
+
+export default function Fallback({ style }) {
+    return <polygon points="-5,4.33 0,-4.33 5,4.33" style={{ ...style, opacity: 0.2 }} />;
+}
+"#;
+            File::create(&fallback_file)
+                .context("creating fallback component")?
+                .write_all(fallback.as_bytes())
+                .context("writing fallback component")?;
+        }
+
+        let error_boundaries_dir = src.join("error_boundaries");
+        let thrower_file = error_boundaries_dir.join("thrower.jsx");
+        let mut remaining_error_boundaries = 0;
+        if self.framework.uses_react_templates() && self.error_boundaries > 0 {
+            remaining_error_boundaries = self.error_boundaries;
+            create_dir_all(&error_boundaries_dir).context("creating error boundaries dir")?;
+            if self.error_boundary_throw {
+                let thrower = r#"let thrown = false;
+
+export default function Thrower() {
+    if (!thrown) {
+        thrown = true;
+        throw new Error("Thrown on the first render");
+    }
+    return null;
+}
+"#;
+                File::create(&thrower_file)
+                    .context("creating thrower component")?
+                    .write_all(thrower.as_bytes())
+                    .context("writing thrower component")?;
+            }
+        }
+
+        let mut queue = VecDeque::new();
+        queue.push_back((
+            src.join(format!("triangle.{}", self.framework.extension())),
+            0,
+        ));
+        remaining_modules -= 1;
+        let mut is_root = true;
+        // The number of modules that can still be placed below the queued
+        // modules, used to keep `max_depth` from swallowing the module budget.
+        let mut queue_capacity = subtree_capacity(0, self.max_depth);
+
+        while let Some((file, depth)) = queue.pop_front() {
+            queue_capacity = queue_capacity.saturating_sub(subtree_capacity(depth, self.max_depth));
+            let is_lazy = lazy_files.contains(&file);
+
+            let at_max_depth = self.max_depth.map_or(false, |max_depth| depth >= max_depth);
+            // Prefer width over leaves when the remaining queue can't take the
+            // rest of the budget anymore.
+            let needs_width = self.max_depth.is_some() && remaining_modules > queue_capacity;
+            // Astro islands can't render `.astro` components.
+            let is_island = is_lazy && self.framework == Framework::Astro;
+            let leaf = remaining_modules == 0
+                || at_max_depth
+                || is_island
+                || (!needs_width
+                    && !queue.is_empty()
+                    && (queue.len() + remaining_modules) % (self.flatness_at(depth) + 1) == 0);
+
+            // The parent imports the module without an extension, so it can
+            // still be changed.
+            let file = if self.extension_mix
+                && self.framework.uses_react_templates()
+                && !app.modules.is_empty()
+            {
+                let extensions = if leaf {
+                    &MIXED_LEAF_EXTENSIONS[..]
+                } else {
+                    &MIXED_CONTAINER_EXTENSIONS[..]
+                };
+                let mixed = file.with_extension(extensions[app.modules.len() % extensions.len()]);
+                if is_lazy {
+                    lazy_files.insert(mixed.clone());
+                }
+                if app.named_exports.remove(&file) {
+                    app.named_exports.insert(mixed.clone());
+                }
+                mixed
+            } else {
+                file
+            };
+            app.modules.push((file.clone(), depth));
+            if !leaf {
+                containers.insert(file.clone());
+            }
+            if is_lazy {
+                app.lazy_modules.push(file.clone());
+            } else {
+                app.eager_modules.push(file.clone());
+            }
+
+            if leaf {
+                let forward_ref = forward_refs.contains(&file);
+                self.write_leaf(&mut app, &mut leaves, &file, forward_ref, is_island)?;
+            } else {
+                let in_subdirectory = decide(remaining_directories, remaining_modules / 3);
+
+                let import_path;
+                let base_file = file.with_extension("");
+                let base_file = if in_subdirectory {
+                    remaining_directories -= 1;
+                    create_dir_all(&base_file).context("creating subdirectory")?;
+                    import_path = format!(
+                        "./{}/triangle_",
+                        base_file.file_name().unwrap().to_str().unwrap()
+                    );
+                    base_file.join("triangle")
+                } else {
+                    import_path =
+                        format!("./{}_", base_file.file_name().unwrap().to_str().unwrap());
+                    base_file
+                };
+
+                let mut children = Vec::with_capacity(3);
+                for i in 1..=3 {
+                    let mut f = base_file.clone();
+                    f.set_file_name(format!(
+                        "{}_{}.{}",
+                        f.file_name().unwrap().to_str().unwrap(),
+                        i,
+                        self.framework.extension()
+                    ));
+                    children.push(f);
+                }
+                remaining_modules = remaining_modules.saturating_sub(3);
+
+                // The React templates decide which children are lazy while
+                // rendering their imports.
+                if matches!(
+                    self.framework,
+                    Framework::Astro | Framework::WebComponents | Framework::Vue
+                ) {
+                    for (i, child) in children.iter_mut().enumerate() {
+                        if decide_early(remaining_dynamic_imports, remaining_modules + (2 - i)) {
+                            remaining_dynamic_imports -= 1;
+                            // Astro islands have to be React components.
+                            if self.framework == Framework::Astro {
+                                child.set_extension("jsx");
+                            }
+                            lazy_files.insert(child.clone());
+                        }
+                    }
+                }
+                max_fanout = max_fanout.max(children.len());
+                for child in &children {
+                    queue.push_back((child.clone(), depth + 1));
+                    queue_capacity =
+                        queue_capacity.saturating_add(subtree_capacity(depth + 1, self.max_depth));
+                }
+
+                let content =
+                    match self.framework {
+                        Framework::Astro => Some(self.with_license_header(astro_container(
+                            &file,
+                            &children,
+                            &lazy_files,
+                        ))),
+                        Framework::WebComponents => Some(self.finish_module(
+                            web_component_container(&src, &file, &children, &lazy_files),
+                        )),
+                        Framework::Vue => Some(self.finish_vue_component(vue_container(
+                            &file,
+                            &children,
+                            &lazy_files,
+                            self.typescript,
+                        ))),
+                        Framework::Qwik => Some(self.finish_module(qwik_container(&import_path))),
+                        _ => None,
+                    };
+                if let Some(content) = content {
+                    write_container(&file, &content)?;
+                    continue;
+                }
+
+                let suspense = if self.suspense_fallback {
+                    "<React.Suspense fallback={<Fallback style={style} />}>"
+                } else {
+                    "<React.Suspense>"
+                };
+                let mut has_lazy_children = false;
+                if let [(a, a_), (b, b_), (c, c_)] = &*[("A", "1"), ("B", "2"), ("C", "3")]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (name, n))| {
+                        export_decisions += 1;
+                        let named = self.framework.uses_react_templates()
+                            && (app.named_exports.len() as f32)
+                                < self.named_export_ratio * export_decisions as f32;
+                        if named {
+                            app.named_exports.insert(children[i].clone());
+                        }
+                        let forward_ref = self.framework.uses_react_templates()
+                            && !self.extension_mix
+                            && (forward_refs.len() as f32)
+                                < self.forward_ref_ratio * export_decisions as f32;
+                        let ref_attribute = if forward_ref {
+                            forward_refs.insert(children[i].clone());
+                            format!(" ref={{ref{name}}}")
+                        } else {
+                            String::new()
+                        };
+                        if decide_early(remaining_dynamic_imports, remaining_modules + (2 - i)) {
+                            remaining_dynamic_imports -= 1;
+                            lazy_files.insert(children[i].clone());
+                            has_lazy_children = true;
+                            if self.framework == Framework::Solid {
+                                return (
+                                    format!(
+                                        "const {name}Lazy = lazy(() => \
+                                         import('{import_path}{n}'));"
+                                    ),
+                                    format!(
+                                        "<Suspense><{name}Lazy style={{props.style}} /></Suspense>"
+                                    ),
+                                );
+                            }
+                            let import = if named {
+                                format!(
+                                    "import('{import_path}{n}').then(({{ Triangle }}) => ({{ \
+                                     default: Triangle }}))"
+                                )
+                            } else {
+                                format!("import('{import_path}{n}')")
+                            };
+                            (
+                                format!("const {name}Lazy = React.lazy(() => {import});"),
+                                format!(
+                                    "{suspense}<{name}Lazy style={{style}}{ref_attribute} \
+                                     /></React.Suspense>"
+                                ),
+                            )
+                        } else if self.framework == Framework::Solid {
+                            (
+                                format!("import {name} from '{import_path}{n}'"),
+                                format!("<{name} style={{props.style}} />"),
+                            )
+                        } else {
+                            let import = if named {
+                                format!("import {{ Triangle as {name} }}")
+                            } else {
+                                format!("import {name}")
+                            };
+                            (
+                                format!("{import} from '{import_path}{n}'"),
+                                format!("<{name} style={{style}}{ref_attribute} />"),
+                            )
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                {
+                    if self.framework == Framework::Solid {
+                        let solid_imports = if has_lazy_children {
+                            "import { Suspense, lazy } from \"solid-js\";\n"
+                        } else {
+                            ""
+                        };
+                        let content = format!(
+                            r#"{solid_imports}{a}
+{b}
+{c}
+
+export default function Container(props) {{
+    return <>
+        <g transform="translate(0 -2.16)   scale(0.5 0.5)">
+            {a_}
+        </g>
+        <g transform="translate(-2.5 2.16) scale(0.5 0.5)">
+            {b_}
+        </g>
+        <g transform="translate(2.5 2.16)  scale(0.5 0.5)">
+            {c_}
+        </g>
+    </>;
+}}
+"#
+                        );
+                        write_container(&file, &self.finish_module(content))?;
+                        continue;
+                    }
+                    let mut extra_imports = String::new();
+                    if has_lazy_children && self.suspense_fallback {
+                        extra_imports.push_str(&format!(
+                            "import Fallback from \"{}\";\n",
+                            relative_import(&file, &fallback_file)
+                        ));
+                    }
+                    let extra = if is_root {
+                        is_root = false;
+                        extra_imports.push_str("import Detector from \"./detector.jsx\";\n");
+                        "\n        <Detector />"
+                    } else {
+                        ""
+                    };
+                    let a_ = if decide(remaining_error_boundaries, remaining_modules / 3) {
+                        let i = self.error_boundaries - remaining_error_boundaries;
+                        remaining_error_boundaries -= 1;
+                        let boundary_file = self.write_error_boundary(&error_boundaries_dir, i)?;
+                        extra_imports.push_str(&format!(
+                            "import ErrorBoundary from \"{}\";\n",
+                            relative_import(&file, &boundary_file)
+                        ));
+                        app.error_boundaries.push(boundary_file);
+                        let thrower = if i == 0 && self.error_boundary_throw {
+                            extra_imports.push_str(&format!(
+                                "import Thrower from \"{}\";\n",
+                                relative_import(&file, &thrower_file)
+                            ));
+                            "<Thrower />"
+                        } else {
+                            ""
+                        };
+                        format!("<ErrorBoundary style={{style}}>{thrower}{a_}</ErrorBoundary>")
+                    } else {
+                        a_.to_string()
+                    };
+                    let child_refs = children
+                        .iter()
+                        .zip(["A", "B", "C"])
+                        .filter(|(child, _)| forward_refs.contains(*child))
+                        .map(|(_, name)| format!("    const ref{name} = React.useRef(null);\n"))
+                        .collect::<String>();
+                    // A forwarded ref needs an element to point to.
+                    let (open_container, close_container, open_fragment, close_fragment) =
+                        if forward_refs.contains(&file) {
+                            (
+                                "const Container = React.forwardRef(function Container({ style }, \
+                                 ref) {",
+                                "});",
+                                "<g ref={ref}>",
+                                "</g>",
+                            )
+                        } else {
+                            let (open_fragment, close_fragment) =
+                                self.fragment_style.tags(containers.len() - 1);
+                            (
+                                "function Container({ style }) {",
+                                "}",
+                                open_fragment,
+                                close_fragment,
+                            )
+                        };
+                    let content = format!(
+                        r#"import React from "react";
+{a}
+{b}
+{c}
+{extra_imports}
+{open_container}
+{child_refs}    return {open_fragment}
+        <g transform="translate(0 -2.16)   scale(0.5 0.5)">
+            {a_}
+        </g>
+        <g transform="translate(-2.5 2.16) scale(0.5 0.5)">
+            {b_}
+        </g>
+        <g transform="translate(2.5 2.16)  scale(0.5 0.5)">
+            {c_}
+        </g>{extra}
+    {close_fragment};
+{close_container}
+
+export default React.memo(Container);
+"#
+                    );
+                    let content = if app.named_exports.contains(&file) {
+                        into_named_export(content)
+                    } else {
+                        content
+                    };
+                    write_container(&file, &self.finish_module(content))?;
+                } else {
+                    unreachable!()
+                }
+            }
+        }
+        app.unplaced_modules = remaining_modules;
+
+        if self.next_public_env_count > 0 {
+            let required = self.write_next_config(&path)?;
+            leaves.env_vars.extend(required);
+        }
+
+        if !leaves.env_vars.is_empty() {
+            let dotenv = leaves
+                .env_vars
+                .iter()
+                .enumerate()
+                .map(|(i, env_var)| format!("{env_var}=Triangle {i}\n"))
+                .collect::<String>();
+            File::create(path.join(".env"))
+                .context("creating .env")?
+                .write_all(dotenv.as_bytes())
+                .context("writing .env")?;
+        }
+
+        let mut css_import = String::new();
+        if self.css_import_depth > 0 {
+            self.write_css_import_chain(&mut app, &src)?;
+            css_import.push_str("import \"./styles/import_0.css\";\n");
+        }
+
+        if self.css_theme_vars > 0 {
+            self.write_css_theme(&src)?;
+            css_import.push_str("import \"./styles/theme.css\";\n");
+            css_import.push_str("import \"./styles/components.css\";\n");
+        }
+
+        if self.tailwind {
+            self.write_tailwind_config(&path, &src)?;
+            css_import.push_str("import \"./globals.css\";\n");
+        }
+
+        if self.public_file_count > 0 {
+            self.write_public_files(&mut app, &path)?;
+        }
+
+        if self.subpath_exports && self.framework.uses_react_templates() {
+            self.write_shared_package(&app)?;
+        }
+
+        match self.framework {
+            Framework::React | Framework::Preact => {
+                self.write_react_entries(&mut app, &css_import)?
+            }
+            Framework::Qwik => self.write_qwik_entries(&app, &css_import)?,
+            Framework::Solid => self.write_solid_entries(&app, &css_import)?,
+            Framework::Astro => self.write_astro_entries(&app, &css_import)?,
+            Framework::Remix => self.write_remix_entries(&app, &css_import)?,
+            Framework::WebComponents => self.write_web_components_entries(&app, &css_import)?,
+            Framework::Vue => self.write_vue_entries(&app, &css_import)?,
+        }
+
+        if self.middleware {
+            self.write_middleware(&path, &src)?;
+        }
+
+        if self.scaffold_repo {
+            self.write_repo_scaffold(&path)?;
+        }
+
+        if self.framework == Framework::Preact {
+            self.write_preact_configs(&path)?;
+        }
+
+        if self.dockerfile {
+            self.write_dockerfile(&path)?;
+        }
+
+        if let Some(package_json_config) = &self.package_json {
+            self.write_package_json(&path, package_json_config)?;
+        }
+
+        let depths = app.modules.iter().map(|(_, depth)| *depth);
+        app.tree_stats = TreeStats {
+            max_depth: depths.clone().max().unwrap_or_default(),
+            avg_depth: depths.sum::<usize>() as f64 / app.modules.len() as f64,
+            max_fanout,
+            leaf_count: app.modules.len() - containers.len(),
+            internal_count: containers.len(),
+        };
+
+        if self.descriptive_names {
+            self.rename_modules(&mut app, &containers)?;
+        }
+
+        Ok(app)
+    }
+
+    /// Renames the component modules to `d{depth}_{role}_{index}`, e.g.
+    /// `d2_container_005.jsx`, and rewrites the specifiers importing them.
+    /// The subdirectories keep their names.
+    fn rename_modules(&self, app: &mut TestApp, containers: &HashSet<PathBuf>) -> Result<()> {
+        let renames = app
+            .modules
+            .iter()
+            .enumerate()
+            .map(|(index, (file, depth))| {
+                let role = if containers.contains(file) {
+                    "container"
+                } else {
+                    "leaf"
+                };
+                let extension = file.extension().unwrap().to_str().unwrap();
+                let renamed =
+                    file.with_file_name(format!("d{depth}_{role}_{index:03}.{extension}"));
+                (file.clone(), renamed)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut files = Vec::new();
+        collect_files(&app.path().join("src"), &mut files)?;
+        collect_files(&app.path().join("app"), &mut files)?;
+        for file in files {
+            let is_script = file.extension().map_or(false, |extension| {
+                VERIFIED_EXTENSIONS.contains(&extension.to_str().unwrap_or_default())
+            });
+            if !is_script {
+                continue;
+            }
+            let content = read_to_string(&file).context("reading module")?;
+            let rewritten = rewrite_specifiers(&file, &content, &renames);
+            if rewritten != content {
+                File::create(&file)
+                    .context("creating module")?
+                    .write_all(rewritten.as_bytes())
+                    .context("writing module")?;
+            }
+        }
+
+        for (from, to) in &renames {
+            rename(from, to).context("renaming module")?;
+        }
+        for (file, _) in &mut app.modules {
+            *file = renames[file].clone();
+        }
+        for file in app.lazy_modules.iter_mut().chain(&mut app.eager_modules) {
+            *file = renames[file].clone();
+        }
+        app.named_exports = app
+            .named_exports
+            .iter()
+            .map(|file| renames[file].clone())
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the flatness of the modules at `depth`.
+    fn flatness_at(&self, depth: usize) -> usize {
+        self.flatness_profile
+            .get(depth)
+            .or_else(|| self.flatness_profile.last())
+            .copied()
+            .unwrap_or(self.flatness)
+    }
+
+    /// Applies the `license_header` and `realistic_boilerplate` options to a
+    /// component module.
+    pub(super) fn finish_module(&self, content: String) -> String {
+        let content = self.with_license_header(content);
+        if self.realistic_boilerplate {
+            content + REALISTIC_BOILERPLATE
+        } else {
+            content
+        }
+    }
+
+    /// Applies [Self::finish_module] to the `<script setup>` block of a Vue
+    /// component, i.e. the part of `content` before the closing `</script>`,
+    /// and opens the block.
+    pub(super) fn finish_vue_component(&self, content: String) -> String {
+        let (script, rest) = content.split_once("</script>").unwrap();
+        let lang = if self.typescript { " lang=\"ts\"" } else { "" };
+        let script = self.finish_module(script.to_string());
+        format!("<script setup{lang}>\n{script}</script>{rest}")
+    }
+
+    /// Prepends the `license_header` to a component module as a `/*!` comment.
+    /// Astro components get the header at the start of their frontmatter, as
+    /// nothing may precede it.
+    fn with_license_header(&self, content: String) -> String {
+        let header = match &self.license_header {
+            Some(header) => header,
+            None => return content,
+        };
+        let mut comment = "/*!\n".to_string();
+        for line in header.lines() {
+            if line.is_empty() {
+                comment.push_str(" *\n");
+            } else {
+                comment.push_str(&format!(" * {line}\n"));
+            }
+        }
+        comment.push_str(" */\n");
+        match content.strip_prefix("---\n") {
+            Some(rest) => format!("---\n{comment}{rest}"),
+            None => comment + &content,
+        }
+    }
+}
+
+/// Configuration struct to generate the `package.json` file of the test app.
+#[derive(Debug)]
+pub struct PackageJsonConfig {
+    /// The version of React to use.
+    pub react_version: String,
+    /// The version of Preact to use with [Framework::Preact].
+    pub preact_version: String,
+    /// The version of Qwik and Qwik City to use with [Framework::Qwik].
+    pub qwik_version: String,
+    /// The version of SolidJS to use with [Framework::Solid].
+    pub solid_version: String,
+    /// The version of Vue to use with [Framework::Vue].
+    pub vue_version: String,
+    /// The version of Astro to use with [Framework::Astro].
+    pub astro_version: String,
+    /// The version of the Astro React integration rendering the islands of
+    /// [Framework::Astro].
+    pub astro_react_version: String,
+    /// The version of the Remix packages to use with [Framework::Remix].
+    pub remix_version: String,
+    /// The version of styled-components to use with
+    /// [TestAppBuilder::styled_components].
+    pub styled_components_version: String,
+    /// The `scripts` of the `package.json`, e.g. `dev` or `build`.
+    pub scripts: IndexMap<String, String>,
+    /// Additional `devDependencies`, mapping package names to versions.
+    pub dev_dependencies: IndexMap<String, String>,
+    /// Additional `dependencies`, mapping package names to versions. They are
+    /// imported by the component modules with
+    /// [TestAppBuilder::resolve_real_deps].
+    pub extra_dependencies: IndexMap<String, String>,
+}
+
+impl Default for PackageJsonConfig {
+    fn default() -> Self {
+        Self {
+            react_version: "^18.2.0".to_string(),
+            preact_version: "^10.11.3".to_string(),
+            qwik_version: "^0.15.2".to_string(),
+            solid_version: "^1.6.2".to_string(),
+            vue_version: "^3.2.45".to_string(),
+            astro_version: "^1.6.10".to_string(),
+            astro_react_version: "^1.2.2".to_string(),
+            remix_version: "^1.8.2".to_string(),
+            styled_components_version: "^5.3.6".to_string(),
+            scripts: IndexMap::new(),
+            dev_dependencies: IndexMap::new(),
+            extra_dependencies: IndexMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        fs::{create_dir_all, read_to_string, write},
+    };
+
+    use super::{collect_entries, FragmentStyle, Framework, TestAppBuilder, MIXED_LEAF_EXTENSIONS};
+
+    #[test]
+    fn build_into_uses_the_given_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = TestAppBuilder {
+            module_count: 10,
+            ..Default::default()
+        }
+        .build_into(dir.path())
+        .unwrap();
+
+        assert_eq!(app.path(), dir.path());
+        assert!(dir.path().join("src/triangle.jsx").exists());
+        assert!(app
+            .modules()
+            .iter()
+            .all(|(module, _)| module.starts_with(dir.path())));
+    }
+
+    #[test]
+    fn flatness_profile_applies_per_depth() {
+        let modules_per_depth = |flatness_profile: Vec<usize>| {
+            let app = TestAppBuilder {
+                module_count: 100,
+                flatness_profile,
+                ..Default::default()
+            }
+            .build()
+            .unwrap();
+            let mut counts = Vec::new();
+            for (_, depth) in app.modules() {
+                if counts.len() <= *depth {
+                    counts.resize(*depth + 1, 0);
+                }
+                counts[*depth] += 1;
+            }
+            counts
+        };
+
+        // Only the last module of each level has children, the tree is a
+        // deep spine.
+        let deep = modules_per_depth(vec![1000, 0]);
+        assert_eq!(deep[0], 1);
+        assert!(deep[1..].iter().all(|count| *count == 3));
+        assert_eq!(deep.len(), 34);
+
+        // Every module above the last level has children.
+        let wide = modules_per_depth(vec![0, 1000]);
+        assert_eq!(wide, vec![1, 3, 9, 27, 60]);
+    }
+
+    #[test]
+    fn max_depth_limits_module_depth() {
+        let app = TestAppBuilder {
+            module_count: 200,
+            flatness: 1,
+            max_depth: Some(5),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert!(app.modules().iter().all(|(_, depth)| *depth <= 5));
+        assert!(app.modules().iter().any(|(_, depth)| *depth == 5));
+        assert_eq!(app.unplaced_modules(), 0);
+    }
+
+    #[test]
+    fn max_depth_reports_unplaced_modules() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            max_depth: Some(2),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert!(app.modules().iter().all(|(_, depth)| *depth <= 2));
+        assert!(app.unplaced_modules() > 0);
+    }
+
+    #[test]
+    fn lazy_modules_match_dynamic_import_count() {
+        let app = TestAppBuilder {
+            module_count: 200,
+            dynamic_import_count: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        assert_eq!(app.lazy_modules().len(), 10);
+        assert_eq!(
+            app.lazy_modules().len() + app.eager_modules().len(),
+            app.modules().len()
+        );
+        let lazy_imports = app
+            .modules()
+            .iter()
+            .map(|(module, _)| {
+                read_to_string(module)
+                    .unwrap()
+                    .matches("React.lazy")
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(lazy_imports, app.lazy_modules().len());
+    }
+
+    #[test]
+    fn extension_mix_uses_different_extensions() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            dynamic_import_count: 5,
+            named_export_ratio: 0.3,
+            extension_mix: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let extensions = app
+            .modules()
+            .iter()
+            .map(|(module, _)| module.extension().unwrap().to_str().unwrap())
+            .collect::<HashSet<_>>();
+        assert!(extensions.len() >= 4, "{extensions:?}");
+        for (module, _) in app.modules() {
+            assert!(module.exists());
+            let content = read_to_string(module).unwrap();
+            // Only modules with children import other modules with `'`, and
+            // they do so without an extension.
+            for specifier in content.split('\'').skip(1).step_by(2) {
+                let base = module.parent().unwrap().join(specifier);
+                let candidates = MIXED_LEAF_EXTENSIONS
+                    .iter()
+                    .filter(|ext| base.with_extension(ext).exists())
+                    .count();
+                assert_eq!(candidates, 1, "{specifier} in {}", module.display());
+            }
+            if module.extension().unwrap() == "cjs" {
+                assert!(content.contains("module.exports"));
+                assert!(!content.contains("<polygon"));
+            }
+        }
+    }
+
+    #[test]
+    fn fragment_style_controls_the_container_fragments() {
+        let container_returns = |fragment_style| {
+            let app = TestAppBuilder {
+                module_count: 50,
+                fragment_style,
+                ..Default::default()
+            }
+            .build()
+            .unwrap();
+            app.modules()
+                .iter()
+                .map(|(module, _)| read_to_string(module).unwrap())
+                .filter(|content| content.contains("function Container("))
+                .map(|content| {
+                    let start = content.find("    return ").unwrap();
+                    content[start..].lines().next().unwrap().to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let shorthand = container_returns(FragmentStyle::Shorthand);
+        assert!(!shorthand.is_empty());
+        assert!(shorthand.iter().all(|line| line == "    return <>"));
+
+        let explicit = container_returns(FragmentStyle::Explicit);
+        assert!(explicit
+            .iter()
+            .all(|line| line == "    return <React.Fragment>"));
+
+        let mixed = container_returns(FragmentStyle::Mixed);
+        assert!(mixed.iter().any(|line| line == "    return <>"));
+        assert!(mixed
+            .iter()
+            .any(|line| line == "    return <React.Fragment>"));
+    }
+
+    #[test]
+    fn license_header_starts_every_module() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            license_header: Some("Copyright (c) Example\n\nMIT License".to_string()),
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        for (module, _) in app.modules() {
+            let content = read_to_string(module).unwrap();
+            assert!(
+                content.starts_with("/*!\n * Copyright (c) Example\n *\n * MIT License\n */\n"),
+                "{} doesn't start with the license header",
+                module.display()
+            );
+        }
+    }
+
+    #[test]
+    fn descriptive_names_encode_depth_and_role() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            dynamic_import_count: 5,
+            descriptive_names: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let modules = app.modules();
+        for (module, depth) in modules {
+            let name = module.file_stem().unwrap().to_str().unwrap();
+            let role = if read_to_string(module).unwrap().contains("<A") {
+                "container"
+            } else {
+                "leaf"
+            };
+            assert!(
+                name.starts_with(&format!("d{depth}_{role}_")),
+                "{name} doesn't encode depth {depth} and role {role}"
+            );
+        }
+        assert!(modules
+            .iter()
+            .any(|(module, _)| module.to_str().unwrap().contains("_leaf_")));
+
+        let (root, _) = &modules[0];
+        let (child, _) = &modules[1];
+        let bootstrap = read_to_string(app.path().join("src/index.jsx")).unwrap();
+        assert!(bootstrap.contains(&format!(
+            "import Triangle from \"./{}\";",
+            root.file_name().unwrap().to_str().unwrap()
+        )));
+        let root_content = read_to_string(root).unwrap();
+        let child_name = child.file_stem().unwrap().to_str().unwrap();
+        assert!(root_content.contains(&format!("/{child_name}'")));
+    }
+
+    #[test]
+    fn descriptive_names_keep_imports_resolvable() {
+        for framework in [
+            Framework::React,
+            Framework::Solid,
+            Framework::Astro,
+            Framework::WebComponents,
+            Framework::Vue,
+        ] {
+            let app = TestAppBuilder {
+                module_count: 100,
+                directories_count: 10,
+                dynamic_import_count: 5,
+                descriptive_names: true,
+                framework,
+                ..Default::default()
+            }
+            .build()
+            .unwrap();
+            assert_eq!(app.verify().unwrap(), vec![], "{framework:?}");
+        }
+    }
+
+    #[test]
+    fn failed_builds_remove_created_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("app");
+        create_dir_all(target.join("src")).unwrap();
+        write(target.join("keep.txt"), "keep").unwrap();
+        // The CSS import chain is written after the modules, so the build fails
+        // partway through.
+        write(target.join("src/styles"), "not a dir").unwrap();
+        let builder = TestAppBuilder {
+            target: Some(target.clone()),
+            module_count: 50,
+            css_import_depth: 2,
+            ..Default::default()
+        };
+        assert!(builder.build().is_err());
+
+        let mut entries = HashSet::new();
+        collect_entries(&target, &mut entries).unwrap();
+        assert_eq!(
+            entries,
+            HashSet::from([
+                target.clone(),
+                target.join("keep.txt"),
+                target.join("src"),
+                target.join("src/styles"),
+            ])
+        );
+        assert_eq!(read_to_string(target.join("keep.txt")).unwrap(), "keep");
+
+        assert!(TestAppBuilder {
+            cleanup_on_error: false,
+            ..builder
+        }
+        .build()
+        .is_err());
+        assert!(target.join("src/triangle.jsx").exists());
+
+        let missing = dir.path().join("missing");
+        assert!(TestAppBuilder {
+            target: Some(missing.clone()),
+            module_count: 10,
+            route_groups: vec!["a/b".to_string()],
+            ..Default::default()
+        }
+        .build()
+        .is_err());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn config_hash_ignores_target() {
+        let a = TestAppBuilder {
+            target: Some("/tmp/a".into()),
+            module_count: 100,
+            ..Default::default()
+        };
+        let b = TestAppBuilder {
+            target: Some("/tmp/b".into()),
+            module_count: 100,
+            ..Default::default()
+        };
+        assert_eq!(a.config_hash(), b.config_hash());
+        assert_eq!(a.config_hash(), a.config_hash());
+    }
+
+    #[test]
+    fn config_hash_changes_with_options() {
+        let base = TestAppBuilder {
+            module_count: 100,
+            ..Default::default()
+        };
+        let changed = [
+            TestAppBuilder {
+                module_count: 101,
+                ..Default::default()
+            },
+            TestAppBuilder {
+                module_count: 100,
+                flatness: 2,
+                ..Default::default()
+            },
+            TestAppBuilder {
+                module_count: 100,
+                max_depth: Some(3),
+                ..Default::default()
+            },
+            TestAppBuilder {
+                module_count: 100,
+                named_export_ratio: 0.5,
+                ..Default::default()
+            },
+            TestAppBuilder {
+                module_count: 100,
+                framework: Framework::Solid,
+                ..Default::default()
+            },
+            TestAppBuilder {
+                module_count: 100,
+                package_json: None,
+                ..Default::default()
+            },
+        ];
+        let mut hashes = HashSet::new();
+        hashes.insert(base.config_hash());
+        for builder in changed {
+            assert!(hashes.insert(builder.config_hash()), "{builder:?}");
+        }
+    }
+
+    #[test]
+    fn suspense_boundaries_get_fallbacks() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            dynamic_import_count: 5,
+            suspense_fallback: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let fallback = read_to_string(app.path().join("src/fallback.jsx")).unwrap();
+        assert!(fallback.contains("export default function Fallback"));
+        let containers = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .filter(|module| module.contains("React.Suspense"))
+            .collect::<Vec<_>>();
+        assert!(!containers.is_empty());
+        for container in containers {
+            assert!(container.contains("import Fallback from \""));
+            let boundaries = container.matches("<React.Suspense").count();
+            let fallbacks = container
+                .matches("<React.Suspense fallback={<Fallback style={style} />}>")
+                .count();
+            assert_eq!(boundaries, fallbacks);
+        }
+    }
+
+    #[test]
+    fn named_exports_match_ratio_and_imports() {
+        let app = TestAppBuilder {
+            module_count: 200,
+            dynamic_import_count: 10,
+            named_export_ratio: 0.25,
+            pages_router_page_count: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let modules = app
+            .modules()
+            .iter()
+            .map(|(module, _)| read_to_string(module).unwrap())
+            .collect::<Vec<_>>();
+        let named_exports = modules
+            .iter()
+            .filter(|module| module.contains("as Triangle };"))
+            .count();
+        let default_exports = modules
+            .iter()
+            .filter(|module| module.contains("export default"))
+            .count();
+        assert_eq!(named_exports + default_exports, modules.len());
+        let expected = 0.25 * (modules.len() - 1) as f32;
+        assert!((named_exports as f32 - expected).abs() <= 1.0);
+
+        let named_imports = modules
+            .iter()
+            .map(|module| {
+                module.matches("import { Triangle as ").count()
+                    + module.matches(".then(({ Triangle })").count()
+            })
+            .sum::<usize>();
+        assert_eq!(named_imports, named_exports);
+        assert!(modules
+            .iter()
+            .any(|module| module.contains(".then(({ Triangle })")));
+        for page in app.pages() {
+            let page = read_to_string(page).unwrap();
+            let (import, _) = page.lines().nth(1).unwrap().split_once(" from ").unwrap();
+            assert!(import == "import Component" || import == "import { Triangle as Component }");
+        }
+    }
+
+    #[test]
+    fn realistic_boilerplate_is_appended_to_modules() {
+        let app = TestAppBuilder {
+            module_count: 50,
+            dynamic_import_count: 2,
+            named_export_ratio: 0.5,
+            realistic_boilerplate: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        for (module, _) in app.modules() {
+            let content = read_to_string(module).unwrap();
+            assert!(content.contains("\nfunction formatTrianglePoints(scale) {"));
+            assert!(content.contains("/**\n * Clamps `value`"));
+            assert!(
+                content.contains("export default React.memo(")
+                    || content.contains(" as Triangle };")
+            );
+        }
+    }
+}
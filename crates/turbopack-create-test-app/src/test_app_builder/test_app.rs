@@ -0,0 +1,589 @@
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    fs::{create_dir_all, read_to_string, File},
+    io::prelude::*,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use tempfile::TempDir;
+
+use super::collect_entries;
+
+/// The extensions of the files scanned by [TestApp::verify] and rewritten by
+/// `descriptive_names`, which are also tried when resolving a specifier
+/// without an extension.
+pub(super) const VERIFIED_EXTENSIONS: [&str; 8] =
+    ["js", "jsx", "ts", "tsx", "mjs", "cjs", "astro", "vue"];
+
+/// Returns the relative specifiers of the static imports, re-exports,
+/// `import()` calls and `require()` calls in `content`. Specifiers built at
+/// runtime, e.g. template literals, are skipped.
+pub(super) fn relative_import_specifiers(content: &str) -> Vec<&str> {
+    let mut specifiers = Vec::new();
+    for keyword in ["from", "import", "import(", "require("] {
+        for (index, _) in content.match_indices(keyword) {
+            let preceded_by_identifier = content[..index].chars().next_back().map_or(false, |c| {
+                c.is_alphanumeric() || c == '_' || c == '$' || c == '.'
+            });
+            if preceded_by_identifier {
+                continue;
+            }
+            let rest = content[index + keyword.len()..].trim_start();
+            let quote = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => continue,
+            };
+            let specifier = match rest[1..].split_once(quote) {
+                Some((specifier, _)) => specifier,
+                None => continue,
+            };
+            if specifier.starts_with("./") || specifier.starts_with("../") {
+                specifiers.push(specifier);
+            }
+        }
+    }
+    specifiers
+}
+
+/// Whether the relative `specifier` imported by `module` points to an existing
+/// file, either as is, with one of the [VERIFIED_EXTENSIONS] appended or as
+/// the index module of a directory.
+fn resolves(module: &Path, specifier: &str) -> bool {
+    let target = module.parent().unwrap().join(specifier);
+    target.is_file()
+        || VERIFIED_EXTENSIONS.iter().any(|extension| {
+            let mut with_extension = target.clone().into_os_string();
+            with_extension.push(format!(".{extension}"));
+            Path::new(&with_extension).is_file()
+                || target.join(format!("index.{extension}")).is_file()
+        })
+}
+
+/// Metrics of the generated component tree, see [TestApp::tree_stats].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TreeStats {
+    /// The depth of the deepest module. The root module has depth 0.
+    pub max_depth: usize,
+    /// The average depth of all modules.
+    pub avg_depth: f64,
+    /// The highest number of children of a single module.
+    pub max_fanout: usize,
+    /// The number of modules without children.
+    pub leaf_count: usize,
+    /// The number of modules with children.
+    pub internal_count: usize,
+}
+
+/// An import found by [TestApp::verify] that doesn't point to an existing file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationError {
+    /// The module containing the import.
+    pub module: PathBuf,
+    /// The relative specifier of the import.
+    pub specifier: String,
+}
+
+#[derive(Debug)]
+pub(super) enum TestAppTarget {
+    Set(PathBuf),
+    Temp(TempDir),
+}
+
+#[derive(Debug)]
+pub struct TestApp {
+    pub(super) target: TestAppTarget,
+    pub(super) requested_module_count: usize,
+    pub(super) modules: Vec<(PathBuf, usize)>,
+    pub(super) lazy_modules: Vec<PathBuf>,
+    pub(super) eager_modules: Vec<PathBuf>,
+    pub(super) unplaced_modules: usize,
+    pub(super) tree_stats: TreeStats,
+    pub(super) locales: Vec<PathBuf>,
+    pub(super) pages: Vec<PathBuf>,
+    pub(super) graphql_documents: Vec<PathBuf>,
+    pub(super) json_files: Vec<PathBuf>,
+    pub(super) portals: Vec<PathBuf>,
+    pub(super) diamonds: Vec<PathBuf>,
+    pub(super) deep_expressions: Vec<PathBuf>,
+    pub(super) decorated_modules: Vec<PathBuf>,
+    pub(super) type_declarations: Vec<PathBuf>,
+    pub(super) dynamic_variants: Vec<PathBuf>,
+    pub(super) shared_workers: Vec<PathBuf>,
+    pub(super) audio_worklets: Vec<PathBuf>,
+    pub(super) error_boundaries: Vec<PathBuf>,
+    pub(super) nested_layouts: Vec<PathBuf>,
+    pub(super) route_groups: Vec<PathBuf>,
+    pub(super) metadata_routes: Vec<PathBuf>,
+    pub(super) public_files: Vec<PathBuf>,
+    pub(super) css_import_chain: Vec<PathBuf>,
+    pub(super) scss_files: Vec<PathBuf>,
+    pub(super) css_files: Vec<PathBuf>,
+    pub(super) unit_tests: Vec<PathBuf>,
+    /// The modules using a named `Triangle` export.
+    pub(super) named_exports: HashSet<PathBuf>,
+    pub(super) additional_files: Vec<PathBuf>,
+}
+
+impl TestApp {
+    /// Returns the path to the directory containing the app.
+    pub fn path(&self) -> &Path {
+        match &self.target {
+            TestAppTarget::Set(target) => target.as_path(),
+            TestAppTarget::Temp(target) => target.path(),
+        }
+    }
+
+    /// Returns the generated component modules together with their depth in
+    /// the component tree. The root module has depth 0.
+    pub fn modules(&self) -> &[(PathBuf, usize)] {
+        &self.modules
+    }
+
+    /// Returns metrics of the component tree, e.g. to compare the topologies
+    /// generated with different `flatness` settings.
+    pub fn tree_stats(&self) -> TreeStats {
+        self.tree_stats
+    }
+
+    /// Checks that the relative specifiers of the static imports, `import()`
+    /// calls and `require()` calls of every JavaScript, TypeScript, Astro and
+    /// Vue module of the app point to existing files, without running a
+    /// bundler. Returns the dangling imports, which are empty for a valid app.
+    pub fn verify(&self) -> Result<Vec<VerificationError>> {
+        let mut entries = HashSet::new();
+        collect_entries(self.path(), &mut entries)?;
+        let mut modules = entries
+            .into_iter()
+            .filter(|entry| {
+                entry.is_file()
+                    && entry.extension().map_or(false, |extension| {
+                        VERIFIED_EXTENSIONS.contains(&extension.to_str().unwrap_or_default())
+                    })
+            })
+            .collect::<Vec<_>>();
+        modules.sort();
+        let mut errors = Vec::new();
+        for module in modules {
+            let content =
+                read_to_string(&module).with_context(|| format!("reading {}", module.display()))?;
+            for specifier in relative_import_specifiers(&content) {
+                if !resolves(&module, specifier) {
+                    errors.push(VerificationError {
+                        module: module.clone(),
+                        specifier: specifier.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Returns the generated component modules grouped by their depth in the
+    /// component tree, in order of depth. Depth 0 only contains the root
+    /// module.
+    pub fn modules_by_depth(&self) -> IndexMap<usize, Vec<PathBuf>> {
+        let mut modules_by_depth = IndexMap::<_, Vec<_>>::new();
+        for (module, depth) in &self.modules {
+            modules_by_depth
+                .entry(*depth)
+                .or_default()
+                .push(module.clone());
+        }
+        modules_by_depth
+    }
+
+    /// Returns the generated component modules that are loaded via
+    /// `React.lazy`/`import()`.
+    pub fn lazy_modules(&self) -> &[PathBuf] {
+        &self.lazy_modules
+    }
+
+    /// Returns the generated component modules that are imported statically.
+    pub fn eager_modules(&self) -> &[PathBuf] {
+        &self.eager_modules
+    }
+
+    /// Returns the requested `module_count` and the number of modules that
+    /// were actually generated. They can differ as children are always added
+    /// in groups of three.
+    pub fn requested_vs_actual(&self) -> (usize, usize) {
+        (self.requested_module_count, self.modules.len())
+    }
+
+    /// Appends a comment to the generated component `module`, which changes its
+    /// content without affecting its behavior, e.g. to trigger a rebuild.
+    /// Every touch appends a different comment.
+    pub fn touch_module(&self, module: &Path) -> Result<()> {
+        let content = read_to_string(module).context("reading module")?;
+        let touches = content.matches("touched ").count() + 1;
+        let comment = if module.extension().map_or(false, |extension| {
+            extension == "astro" || extension == "vue"
+        }) {
+            format!("<!-- touched {touches} -->\n")
+        } else {
+            format!("// touched {touches}\n")
+        };
+        self.replace_module(module, content + &comment)
+    }
+
+    /// Replaces the content of the generated component `module`, e.g. to
+    /// benchmark rebuilds after an edit. The modification time of the module
+    /// always advances, even on file systems with a coarse resolution.
+    pub fn replace_module(&self, module: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+        if !self
+            .modules
+            .iter()
+            .any(|(generated, _)| generated == module)
+        {
+            bail!("{} is not a generated module", module.display());
+        }
+        let modified = module
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .context("reading modification time of module")?;
+        let mut file = File::create(module).context("creating module")?;
+        file.write_all(content.as_ref()).context("writing module")?;
+        if file.metadata().and_then(|metadata| metadata.modified())? <= modified {
+            file.set_modified(modified + Duration::from_secs(1))
+                .context("advancing modification time of module")?;
+        }
+        Ok(())
+    }
+
+    /// Writes a file into the app directory, creating its parent directories.
+    /// `relative_path` must stay inside the app directory.
+    pub fn write_additional_file(
+        &mut self,
+        relative_path: impl AsRef<Path>,
+        content: impl AsRef<[u8]>,
+    ) -> Result<PathBuf> {
+        let relative_path = relative_path.as_ref();
+        if !relative_path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            bail!(
+                "additional file {} must be relative to the app directory",
+                relative_path.display()
+            );
+        }
+        let file = self.path().join(relative_path);
+        if let Some(parent) = file.parent() {
+            create_dir_all(parent).context("creating parent dir of additional file")?;
+        }
+        File::create(&file)
+            .context("creating additional file")?
+            .write_all(content.as_ref())
+            .context("writing additional file")?;
+        self.additional_files.push(file.clone());
+        Ok(file)
+    }
+
+    /// Returns the paths of the files written by
+    /// [TestApp::write_additional_file].
+    pub fn additional_files(&self) -> &[PathBuf] {
+        &self.additional_files
+    }
+
+    /// Returns the number of modules of the requested `module_count` that
+    /// couldn't be placed, e.g. because of `max_depth`.
+    pub fn unplaced_modules(&self) -> usize {
+        self.unplaced_modules
+    }
+
+    /// Returns the paths of the generated locale message catalogs.
+    pub fn locales(&self) -> &[PathBuf] {
+        &self.locales
+    }
+
+    /// Returns the paths of the modules the computed specifiers of
+    /// `dynamic_expression_count` select from.
+    pub fn dynamic_variants(&self) -> &[PathBuf] {
+        &self.dynamic_variants
+    }
+
+    /// Returns the paths of the generated `SharedWorker` modules.
+    pub fn shared_workers(&self) -> &[PathBuf] {
+        &self.shared_workers
+    }
+
+    /// Returns the paths of the generated audio worklet modules.
+    pub fn audio_worklets(&self) -> &[PathBuf] {
+        &self.audio_worklets
+    }
+
+    /// Returns the directories of the app router route groups, e.g.
+    /// `src/app/(marketing)`.
+    pub fn route_groups(&self) -> &[PathBuf] {
+        &self.route_groups
+    }
+
+    /// Returns the paths of the app router metadata routes of
+    /// `metadata_routes`, e.g. `src/app/sitemap.ts`.
+    pub fn metadata_routes(&self) -> &[PathBuf] {
+        &self.metadata_routes
+    }
+
+    /// Returns the paths of the nested app router `layout.jsx` files, starting
+    /// with the outermost one.
+    pub fn nested_layouts(&self) -> &[PathBuf] {
+        &self.nested_layouts
+    }
+
+    /// Returns the paths of the generated `pages/page_N.jsx` pages.
+    pub fn pages(&self) -> &[PathBuf] {
+        &self.pages
+    }
+
+    /// Returns the paths of the generated `.graphql` query documents.
+    pub fn graphql_documents(&self) -> &[PathBuf] {
+        &self.graphql_documents
+    }
+
+    /// Returns the paths of the leaf modules returning a deeply nested
+    /// conditional expression.
+    pub fn deep_expressions(&self) -> &[PathBuf] {
+        &self.deep_expressions
+    }
+
+    /// Returns the paths of the leaf modules rendering into a portal.
+    pub fn portals(&self) -> &[PathBuf] {
+        &self.portals
+    }
+
+    /// Returns the paths of the shared modules of `diamond_count`, each
+    /// imported by two leaf modules.
+    pub fn diamonds(&self) -> &[PathBuf] {
+        &self.diamonds
+    }
+
+    /// Returns the paths of the generated `.json` data files.
+    pub fn json_files(&self) -> &[PathBuf] {
+        &self.json_files
+    }
+
+    /// Returns the paths of the generated modules which only declare types.
+    pub fn type_declarations(&self) -> &[PathBuf] {
+        &self.type_declarations
+    }
+
+    /// Returns the paths of the generated modules using decorators.
+    pub fn decorated_modules(&self) -> &[PathBuf] {
+        &self.decorated_modules
+    }
+
+    /// Returns the paths of the generated error boundaries.
+    pub fn error_boundaries(&self) -> &[PathBuf] {
+        &self.error_boundaries
+    }
+
+    /// Returns the paths of the generated static files in `public/assets`.
+    pub fn public_files(&self) -> &[PathBuf] {
+        &self.public_files
+    }
+
+    /// Returns the paths of the generated CSS `@import` chain, starting with
+    /// the file imported by the bootstrap.
+    pub fn css_import_chain(&self) -> &[PathBuf] {
+        &self.css_import_chain
+    }
+
+    /// Returns the paths of the `.scss` files imported by the leaf modules,
+    /// without the shared `src/styles/_mixins.scss`.
+    pub fn scss_files(&self) -> &[PathBuf] {
+        &self.scss_files
+    }
+
+    /// Returns the paths of the sibling `.css` files of `css_file_count`, the
+    /// ones imported as a CSS module end with `.module.css`.
+    pub fn css_files(&self) -> &[PathBuf] {
+        &self.css_files
+    }
+
+    /// Returns the paths of the generated `*.test.jsx` unit tests.
+    pub fn unit_tests(&self) -> &[PathBuf] {
+        &self.unit_tests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read_to_string, write};
+
+    use super::{relative_import_specifiers, VerificationError};
+    use crate::test_app_builder::{Framework, TestAppBuilder};
+
+    #[test]
+    fn modules_can_be_edited_after_building() {
+        let app = TestAppBuilder {
+            module_count: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let (module, _) = &app.modules()[3];
+        let modified = || module.metadata().unwrap().modified().unwrap();
+        let content = read_to_string(module).unwrap();
+        let before = modified();
+        app.touch_module(module).unwrap();
+        let touched = read_to_string(module).unwrap();
+        assert!(touched.starts_with(&content) && touched != content);
+        assert!(modified() > before);
+
+        let before = modified();
+        app.touch_module(module).unwrap();
+        assert_ne!(read_to_string(module).unwrap(), touched);
+        assert!(modified() > before);
+
+        let before = modified();
+        app.replace_module(module, "export default function Triangle() {}\n")
+            .unwrap();
+        assert_eq!(
+            read_to_string(module).unwrap(),
+            "export default function Triangle() {}\n"
+        );
+        assert!(modified() > before);
+
+        assert!(app
+            .replace_module(&app.path().join("package.json"), "{}")
+            .is_err());
+    }
+
+    #[test]
+    fn modules_by_depth_groups_levels() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            flatness_profile: vec![0, 1000],
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let modules_by_depth = app.modules_by_depth();
+        assert_eq!(
+            modules_by_depth.keys().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(modules_by_depth[&0], vec![app.modules()[0].0.clone()]);
+        let counts = modules_by_depth
+            .values()
+            .map(|modules| modules.len())
+            .collect::<Vec<_>>();
+        assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(counts.iter().sum::<usize>(), app.modules().len());
+    }
+
+    #[test]
+    fn tree_stats_describe_the_component_tree() {
+        let app = TestAppBuilder {
+            module_count: 100,
+            flatness_profile: vec![0, 1000],
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let stats = app.tree_stats();
+        assert_eq!(stats.leaf_count + stats.internal_count, app.modules().len());
+        assert_eq!(app.modules().len(), 100);
+        assert!(stats.max_depth >= 1);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(stats.max_fanout, 3);
+        assert!(stats.avg_depth > 0.0 && stats.avg_depth < stats.max_depth as f64);
+    }
+
+    #[test]
+    fn requested_vs_actual_reports_generated_module_count() {
+        let app = TestAppBuilder {
+            module_count: 1000,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        assert_eq!(app.requested_vs_actual(), (1000, app.modules().len()));
+        assert_eq!(app.modules().len(), 1000);
+
+        // Children are added in groups of three, so not every count can be
+        // reached exactly.
+        let app = TestAppBuilder {
+            module_count: 1001,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        assert_eq!(app.requested_vs_actual(), (1001, app.modules().len()));
+        assert_eq!(app.modules().len(), 1000);
+    }
+
+    #[test]
+    fn verify_reports_dangling_imports() {
+        for framework in [
+            Framework::React,
+            Framework::Solid,
+            Framework::Astro,
+            Framework::WebComponents,
+            Framework::Vue,
+        ] {
+            let app = TestAppBuilder {
+                module_count: 100,
+                directories_count: 10,
+                dynamic_import_count: 5,
+                framework,
+                ..Default::default()
+            }
+            .build()
+            .unwrap();
+            assert_eq!(app.verify().unwrap(), vec![], "{framework:?}");
+        }
+
+        let app = TestAppBuilder {
+            module_count: 100,
+            directories_count: 10,
+            dynamic_import_count: 5,
+            css_file_count: 4,
+            scss: true,
+            extension_mix: true,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+        assert_eq!(app.verify().unwrap(), vec![]);
+
+        let (root, _) = &app.modules()[0];
+        let content = read_to_string(root).unwrap();
+        let specifier = relative_import_specifiers(&content)[0];
+        let moved = format!("{specifier}_moved");
+        write(root, content.replacen(specifier, &moved, 1)).unwrap();
+        assert_eq!(
+            app.verify().unwrap(),
+            vec![VerificationError {
+                module: root.clone(),
+                specifier: moved,
+            }]
+        );
+    }
+
+    #[test]
+    fn additional_files_are_written_and_tracked() {
+        let mut app = TestAppBuilder {
+            module_count: 10,
+            ..Default::default()
+        }
+        .build()
+        .unwrap();
+
+        let file = app
+            .write_additional_file("bench/driver/index.js", "console.log(1);\n")
+            .unwrap();
+        assert_eq!(file, app.path().join("bench/driver/index.js"));
+        assert_eq!(read_to_string(&file).unwrap(), "console.log(1);\n");
+        assert_eq!(app.additional_files(), [file]);
+
+        assert!(app.write_additional_file("../outside.js", "").is_err());
+    }
+}
@@ -2,7 +2,9 @@ use std::future::IntoFuture;
 
 use anyhow::Result;
 use turbo_tasks::primitives::{OptionStringVc, StringVc};
-use turbo_tasks_env::{DotenvProcessEnvVc, EnvMapVc, ProcessEnv, ProcessEnvVc};
+use turbo_tasks_env::{
+    DotenvProcessEnvVc, EnvMapVc, OptionSourcedValueVc, ProcessEnv, ProcessEnvVc,
+};
 use turbo_tasks_fs::FileSystemPathVc;
 
 use crate::ProcessEnvIssue;
@@ -59,4 +61,9 @@ impl ProcessEnv for TryDotenvProcessEnv {
     async fn read(&self, name: &str) -> Result<OptionStringVc> {
         self.with_issue(|e| e.read(name)).await
     }
+
+    #[turbo_tasks::function]
+    async fn read_with_source(&self, name: &str) -> Result<OptionSourcedValueVc> {
+        self.with_issue(|e| e.read_with_source(name)).await
+    }
 }